@@ -4,13 +4,16 @@ use crate::{
   diagnostics::{
     self,
     printer::DiagnosticPrinter,
+    Diagnostic,
     DiagnosticGlossaryCell
   }, 
   symbols, 
+  structs,
   syntax::{
     evaluator::Evaluator,
-    lexer::Lexer,
+    lexer::{Lexer, Token},
     parser::Parser,
+    value::Value,
     SyntaxTree
   }, 
   text::{
@@ -21,18 +24,17 @@ use crate::{
 
 pub struct CompilationUnit {
   pub st: SyntaxTree,
-  pub diagnostic_glossary: DiagnosticGlossaryCell
+  pub diagnostic_glossary: DiagnosticGlossaryCell,
+  text: SourceText
 }
 
 impl CompilationUnit {
 
   pub fn compile(input: &str) -> CompilationUnit {
     let text = text::SourceText::new(input.to_string());
-    let mut lexer = Lexer::new(input); 
-    let mut tokens = Vec::new();
-    while let Some(token) = lexer.next_token(){
-        tokens.push(token);
-    }
+    // Diagnostics
+    let _diagnostic: DiagnosticGlossaryCell = Rc::new(RefCell::new(diagnostics::DiagnosticGlossary::new()));	
+    let tokens: Vec<Token> = Lexer::new(input, Rc::clone(&_diagnostic)).collect();
 
     // Printing Tokens tokenised by Lexer;
     // Debug Logs
@@ -40,8 +42,6 @@ impl CompilationUnit {
     		println!("{:?}", token);
     }
 
-    // Diagnostics
-    let _diagnostic: DiagnosticGlossaryCell = Rc::new(RefCell::new(diagnostics::DiagnosticGlossary::new()));	
     // Parsing Tokens	
     let mut syntax_tree: SyntaxTree = SyntaxTree::new();
     let mut parser = Parser::new(
@@ -55,45 +55,71 @@ impl CompilationUnit {
     syntax_tree.visualise();
 
     if Self::diagnose(&text, &_diagnostic).is_err() {
-      return Self::create_compilation_unit(syntax_tree, _diagnostic);
+      return Self::create_compilation_unit(syntax_tree, _diagnostic, text);
     }
     let mut symbol_checker = symbols::SymbolChecker::new(Rc::clone(&_diagnostic));
+    symbol_checker.declare_functions(&syntax_tree.statements);
     syntax_tree.visit(&mut symbol_checker);
+    let mut struct_checker = structs::StructChecker::new(Rc::clone(&_diagnostic));
+    syntax_tree.visit(&mut struct_checker);
     
     if Self::diagnose(&text, &_diagnostic).is_err() {
-      return Self::create_compilation_unit(syntax_tree, _diagnostic);
+      return Self::create_compilation_unit(syntax_tree, _diagnostic, text);
     }
-    return Self::create_compilation_unit(syntax_tree, _diagnostic);
+    // Only warnings are left at this point
+    Self::print_diagnostics(&text, &_diagnostic.borrow().diagnostics);
+    return Self::create_compilation_unit(syntax_tree, _diagnostic, text);
     
   }
 
   fn diagnose(text: &SourceText, diagnostic_glossary: &DiagnosticGlossaryCell) -> Result<(),()> {
     let diagnositcs_binding = diagnostic_glossary.borrow();
-    if diagnositcs_binding.diagnostics.len() > 0 {
-      let diaprinter = DiagnosticPrinter::new(&text, &diagnositcs_binding.diagnostics);
-      diaprinter.print();
+    if diagnositcs_binding.has_errors() {
+      Self::print_diagnostics(text, &diagnositcs_binding.diagnostics);
       return Err(());
     }
     return Ok(());
   }
 
-  fn create_compilation_unit(st: SyntaxTree, diagnostic_glossary: DiagnosticGlossaryCell) -> CompilationUnit {
+  fn print_diagnostics(text: &SourceText, diagnostics: &[Diagnostic]) {
+    let diaprinter = DiagnosticPrinter::new(text, diagnostics);
+    diaprinter.print();
+  }
+
+  fn create_compilation_unit(st: SyntaxTree, diagnostic_glossary: DiagnosticGlossaryCell, text: SourceText) -> CompilationUnit {
     CompilationUnit {
       st,
-      diagnostic_glossary
+      diagnostic_glossary,
+      text
     }
   }
 
   pub fn prerun(&self) {
-    if self.diagnostic_glossary.borrow().diagnostics.len() > 0 {
+    if self.diagnostic_glossary.borrow().has_errors() {
       return;
     }
     self.run();
   }
 
   pub fn run(&self) {
-    let mut evaluate = Evaluator::new();
+    let compile_diagnostics = self.diagnostic_glossary.borrow().diagnostics.len();
+    let result = self.evaluate();
+    let runtime_diagnostics = &self.diagnostic_glossary.borrow().diagnostics[compile_diagnostics..];
+    if !runtime_diagnostics.is_empty() {
+      Self::print_diagnostics(&self.text, runtime_diagnostics);
+      return;
+    }
+	  println!("Result: {:?}", result);
+  }
+
+  pub fn evaluate(&self) -> Option<Value> {
+    return self.evaluate_with_max_call_depth(Evaluator::DEFAULT_MAX_CALL_DEPTH);
+  }
+
+  pub fn evaluate_with_max_call_depth(&self, max_call_depth: usize) -> Option<Value> {
+    let mut evaluate = Evaluator::new(Rc::clone(&self.diagnostic_glossary)).with_max_call_depth(max_call_depth);
+    evaluate.declare_functions(&self.st.statements);
 	  self.st.visit(&mut evaluate);
-	  println!("Result: {:?}", evaluate.last_value);
-  } 
+    return evaluate.last_value;
+  }
 }
\ No newline at end of file
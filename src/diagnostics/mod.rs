@@ -14,17 +14,31 @@ pub enum DiagnosticKind {
 pub struct Diagnostic {
   pub message: String,
   pub span: TextSpan,
-  pub kind: DiagnosticKind
+  pub kind: DiagnosticKind,
+  // Secondary spans with a note each, pointing at code related to the diagnostic
+  pub labels: Vec<DiagnosticLabel>
+}
+
+#[derive(Clone, Debug)]
+pub struct DiagnosticLabel {
+  pub message: String,
+  pub span: TextSpan
 }
 
 pub type DiagnosticGlossaryCell = Rc<RefCell<DiagnosticGlossary>>;
 
 impl Diagnostic {
   pub fn new(message: String, span: TextSpan, kind: DiagnosticKind) -> Self {
-    return Diagnostic { message, span, kind };
-  }  
+    return Diagnostic { message, span, kind, labels: vec![] };
+  }
+
+  pub fn with_label(mut self, message: String, span: TextSpan) -> Self {
+    self.labels.push(DiagnosticLabel { message, span });
+    return self;
+  }
 }
 
+#[derive(Default)]
 pub struct DiagnosticGlossary {
   pub diagnostics: Vec<Diagnostic>
 }
@@ -44,6 +58,10 @@ impl DiagnosticGlossary {
     self.diagnostics.push(warning);
   }
 
+  pub fn report(&mut self, diagnostic: Diagnostic) {
+    self.diagnostics.push(diagnostic);
+  }
+
   pub fn report_unexpected_token(&mut self, expected: &TokenType, token: &Token) {
     self.report_error(format!("Expected <{}>, found <{}>", expected, token.kind), token.span.clone());
   }
@@ -52,7 +70,211 @@ impl DiagnosticGlossary {
     self.report_error(format!("Expected expression, found <{}>", token.kind), token.span.clone());
   }
 
+  pub fn report_expected_end_of_statement(&mut self, token: &Token) {
+    self.report_error(format!("Expected end of statement, found <{}>", token.kind), token.span.clone());
+  }
+
+  pub fn report_variable_out_of_scope(&mut self, token: &Token, declaration: &TextSpan) {
+    let diagnostic = Diagnostic::new(format!("Variable '{}' is not in scope here", token.span.literal), token.span.clone(), DiagnosticKind::Error)
+      .with_label(format!("'{}' is declared here, in a scope that has ended", declaration.literal), declaration.clone());
+    self.report(diagnostic);
+  }
+
+  pub fn report_invalid_assignment_target(&mut self, span: TextSpan) {
+    self.report_error("Invalid assignment target, only a variable, an element or a field can be assigned to".to_string(), span);
+  }
+
+  pub fn report_assignment_to_immutable(&mut self, token: &Token, declaration: &TextSpan) {
+    let diagnostic = Diagnostic::new(format!("Cannot assign twice to immutable variable '{}'", token.span.literal), token.span.clone(), DiagnosticKind::Error)
+      .with_label(format!("'{}' is declared here, 'let mut' would make it mutable", declaration.literal), declaration.clone());
+    self.report(diagnostic);
+  }
+
+  pub fn report_outside_of_function(&mut self, keyword: &Token) {
+    self.report_error(format!("'{}' outside of a function", keyword.span.literal), keyword.span.clone());
+  }
+
+  pub fn report_duplicate_parameter(&mut self, token: &Token) {
+    self.report_error(format!("Duplicate parameter '{}'", token.span.literal), token.span.clone());
+  }
+
+  // A closure has no name
+  pub fn report_wrong_argument_count(&mut self, span: TextSpan, name: Option<&str>, expected: usize, given: usize, declaration: Option<&TextSpan>) {
+    let plural = |count: usize, noun: &str| if count == 1 { format!("{} {}", count, noun) } else { format!("{} {}s", count, noun) };
+    let callee = match name {
+      Some(name) => format!("Function '{}'", name),
+      None => "Closure".to_string()
+    };
+    let message = format!(
+      "{} takes {} but {} {} given", callee, plural(expected, "argument"), given, if given == 1 { "was" } else { "were" }
+    );
+    let mut diagnostic = Diagnostic::new(message, span, DiagnosticKind::Error);
+    if let Some(declaration) = declaration {
+      diagnostic = diagnostic.with_label(format!("'{}' is declared here", declaration.literal), declaration.clone());
+    }
+    self.report(diagnostic);
+  }
+
+  pub fn report_assignment_to_captured(&mut self, token: &Token, declaration: &TextSpan) {
+    let diagnostic = Diagnostic::new(
+      format!("Cannot assign to '{}', closures capture variables by value", token.span.literal), token.span.clone(), DiagnosticKind::Error
+    ).with_label(format!("'{}' is declared here, outside of the closure", declaration.literal), declaration.clone());
+    self.report(diagnostic);
+  }
+
+  pub fn report_stack_overflow(&mut self, span: TextSpan, max_call_depth: usize) {
+    self.report_error(format!("Stack overflow in script, calls nest deeper than {}", max_call_depth), span);
+  }
+
+  pub fn report_nesting_overflow(&mut self, span: TextSpan, max_depth: usize) {
+    self.report_error(format!("Stack overflow in script, expressions nest deeper than {}", max_depth), span);
+  }
+
+  pub fn report_nesting_too_deep(&mut self, token: &Token, max_depth: usize) {
+    self.report_error(format!("Expressions nest deeper than {}, the rest of the input is skipped", max_depth), token.span.clone());
+  }
+
+  pub fn report_assignment_to_element_of_immutable(&mut self, token: &Token, declaration: &TextSpan) {
+    let diagnostic = Diagnostic::new(
+      format!("Cannot assign to an element of immutable variable '{}'", token.span.literal), token.span.clone(), DiagnosticKind::Error
+    ).with_label(format!("'{}' is declared here, 'let mut' would make it mutable", declaration.literal), declaration.clone());
+    self.report(diagnostic);
+  }
+
+  pub fn report_assignment_to_field_of_immutable(&mut self, token: &Token, declaration: &TextSpan) {
+    let diagnostic = Diagnostic::new(
+      format!("Cannot assign to a field of immutable variable '{}'", token.span.literal), token.span.clone(), DiagnosticKind::Error
+    ).with_label(format!("'{}' is declared here, 'let mut' would make it mutable", declaration.literal), declaration.clone());
+    self.report(diagnostic);
+  }
+
+  pub fn report_duplicate_struct(&mut self, token: &Token, declaration: &TextSpan) {
+    let diagnostic = Diagnostic::new(format!("Struct '{}' is already declared", token.span.literal), token.span.clone(), DiagnosticKind::Error)
+      .with_label(format!("'{}' is first declared here", declaration.literal), declaration.clone());
+    self.report(diagnostic);
+  }
+
+  pub fn report_function_outside_of_top_level(&mut self, token: &Token) {
+    self.report_error(format!("Function '{}' must be declared at the top level", token.span.literal), token.span.clone());
+  }
+
+  pub fn report_struct_outside_of_top_level(&mut self, token: &Token) {
+    self.report_error(format!("Struct '{}' must be declared at the top level", token.span.literal), token.span.clone());
+  }
+
+  pub fn report_undeclared_struct(&mut self, token: &Token) {
+    self.report_error(format!("Undeclared struct '{}'", token.span.literal), token.span.clone());
+  }
+
+  pub fn report_duplicate_field(&mut self, token: &Token, name: &str) {
+    self.report_error(format!("Duplicate field '{}' in struct '{}'", token.span.literal, name), token.span.clone());
+  }
+
+  pub fn report_duplicate_initialiser(&mut self, token: &Token) {
+    self.report_error(format!("Field '{}' is initialised twice", token.span.literal), token.span.clone());
+  }
+
+  pub fn report_unknown_field(&mut self, token: &Token, name: &str, declaration: &TextSpan) {
+    let diagnostic = Diagnostic::new(format!("Struct '{}' has no field '{}'", name, token.span.literal), token.span.clone(), DiagnosticKind::Error)
+      .with_label(format!("'{}' is declared here", declaration.literal), declaration.clone());
+    self.report(diagnostic);
+  }
+
+  pub fn report_missing_initialisers(&mut self, token: &Token, missing: &[&str], declaration: &TextSpan) {
+    let fields = missing.iter().map(|field| format!("'{}'", field)).collect::<Vec<_>>().join(", ");
+    let diagnostic = Diagnostic::new(
+      format!("Missing {} {} in initialiser of struct '{}'", if missing.len() == 1 { "field" } else { "fields" }, fields, token.span.literal),
+      token.span.clone(), DiagnosticKind::Error
+    ).with_label(format!("'{}' is declared here", declaration.literal), declaration.clone());
+    self.report(diagnostic);
+  }
+
+  // Which struct a value is isn't known before it runs, but a field no
+  // struct has can't be there whatever it is
+  pub fn report_field_of_no_struct(&mut self, token: &Token) {
+    self.report_error(format!("No struct declared so far has a field '{}'", token.span.literal), token.span.clone());
+  }
+
+  pub fn report_assignment_to_builtin(&mut self, token: &Token) {
+    self.report_error(format!("Cannot assign to builtin function '{}'", token.span.literal), token.span.clone());
+  }
+
   pub fn report_undeclared_variable(&mut self, token: &Token) {
     self.report_error(format!("Undeclared variable '{}'", token.span.literal), token.span.clone());
   }
+
+  pub fn report_unexpected_character(&mut self, span: TextSpan, c: char, hint: Option<&str>) {
+    let mut message = format!("Unexpected character '{}' (U+{:04X})", c, c as u32);
+    if let Some(hint) = hint {
+      message.push_str(&format!(", did you mean '{}'?", hint));
+    }
+    self.report_error(message, span);
+  }
+
+  pub fn report_unexpected_characters(&mut self, span: TextSpan) {
+    self.report_error(format!("Unexpected characters '{}'", span.literal), span);
+  }
+
+  pub fn report_mixed_script_identifier(&mut self, span: TextSpan, scripts: &[&str]) {
+    self.report_warning(format!("Identifier '{}' mixes {} scripts", span.literal, scripts.join(" and ")), span);
+  }
+
+  pub fn report_confusable_identifier(&mut self, token: &Token, declaration: &TextSpan) {
+    let diagnostic = Diagnostic::new(
+      format!("Identifier '{}' is confusable with '{}'", token.span.literal, declaration.literal), token.span.clone(), DiagnosticKind::Warning
+    ).with_label(format!("'{}' is declared here", declaration.literal), declaration.clone());
+    self.report(diagnostic);
+  }
+
+  pub fn has_errors(&self) -> bool {
+    return self.diagnostics.iter().any(|diagnostic| matches!(diagnostic.kind, DiagnosticKind::Error));
+  }
+
+  pub fn report_unterminated_string(&mut self, span: TextSpan) {
+    self.report_error("Unterminated string literal".to_string(), span);
+  }
+
+  pub fn report_integer_literal_out_of_range(&mut self, span: TextSpan) {
+    self.report_error(format!("Integer literal '{}' out of range, add the 'n' suffix for an arbitrary-precision integer", span.literal), span);
+  }
+
+  pub fn report_float_literal_out_of_range(&mut self, span: TextSpan) {
+    self.report_error(format!("Float literal '{}' out of range", span.literal), span);
+  }
+
+  pub fn report_unterminated_block_comment(&mut self, span: TextSpan) {
+    self.report_error("Unterminated block comment".to_string(), span);
+  }
+
+  pub fn report_unterminated_interpolation(&mut self, span: TextSpan) {
+    self.report_error("Unterminated string interpolation".to_string(), span);
+  }
+
+  pub fn report_unexpected_interpolation_token(&mut self, token: &Token) {
+    self.report_error(format!("Expected <}}> to close interpolation, found <{}>", token.kind), token.span.clone());
+  }
+
+  pub fn report_unknown_escape_sequence(&mut self, span: TextSpan) {
+    self.report_error(format!("Unknown escape sequence '{}'", span.literal), span);
+  }
+
+  pub fn report_invalid_unicode_escape(&mut self, span: TextSpan) {
+    self.report_error(format!("Invalid unicode escape '{}'", span.literal), span);
+  }
+
+  pub fn report_outside_of_loop(&mut self, keyword: &Token) {
+    self.report_error(format!("'{}' outside of a loop", keyword.span.literal), keyword.span.clone());
+  }
+
+  pub fn report_not_iterable(&mut self, span: TextSpan, found: &str) {
+    self.report_error(format!("Expected a range, an array or a map to iterate over, found {}", found), span);
+  }
+
+  pub fn report_empty_range(&mut self, span: TextSpan) {
+    self.report_warning(format!("Range '{}' is empty, the loop never runs", span.literal), span);
+  }
+
+  pub fn report_expected_boolean(&mut self, span: TextSpan, found: &str) {
+    self.report_error(format!("Expected a boolean condition, found {}", found), span);
+  }
 }
\ No newline at end of file
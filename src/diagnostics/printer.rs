@@ -1,6 +1,8 @@
 use std::cmp;
-use termion::color::{Fg, Red, Reset};
-use crate::diagnostics::Diagnostic;
+use termion::color::{Blue, Fg, Red, Reset, Yellow};
+use unicode_width::UnicodeWidthStr;
+use crate::diagnostics::{Diagnostic, DiagnosticKind};
+use crate::syntax::lexer::TextSpan;
 use crate::text::SourceText;
 
 
@@ -9,46 +11,71 @@ pub struct DiagnosticPrinter<'a> {
   diagnostics: &'a [Diagnostic]
 }
 
+// Number of characters shown on either side of the highlighted span
 const PREFIX_LENGTH: usize = 8;
+// Tabs are printed as this many spaces, the terminal would otherwise expand
+// them to a tab stop the pointers know nothing about
+const TAB_WIDTH: usize = 4;
 
 impl <'a> DiagnosticPrinter<'a> {
   pub fn new(text: &'a SourceText, diagnostics: &'a [Diagnostic]) -> Self {
     return Self { text, diagnostics }
   }
 
+  // Labels are rendered like the diagnostic itself, below it in blue
   pub fn stringify_diagnostics(&self, diagnostic: &Diagnostic) -> String {
-    let line_index = self.text.line_index(diagnostic.span.start);
+    let mut result = self.stringify_span(&diagnostic.span, &diagnostic.message, Self::color(diagnostic), '^');
+    for label in &diagnostic.labels {
+      result.push('\n');
+      result.push_str(&self.stringify_span(&label.span, &label.message, Blue.fg_str(), '-'));
+    }
+    return result;
+  }
+
+  fn stringify_span(&self, text_span: &TextSpan, message: &str, color: &str, pointer: char) -> String {
+    let line_index = self.text.line_index(text_span.start);
     let line = self.text.get_line(line_index);
 
+    // Spans are byte offsets, the pointers and the reported column are laid
+    // out by display width so that multi-byte and wide characters keep them
+    // under the right text
     let line_start = self.text.line_start(line_index);
-    let column = diagnostic.span.start - line_start;
-    let (prefix, span, suffix) = self.text_spans(diagnostic, &line, column);
+    let column = cmp::min(text_span.start - line_start, line.len());
+    let (prefix, span, suffix) = self.text_spans(text_span, line, column);
+    let (prefix, span, suffix) = (Self::expand_tabs(prefix), Self::expand_tabs(span), Self::expand_tabs(suffix));
+
+    let indent = prefix.width();
+    let (pointers, pointer_line) = Self::format_pointer(&span, indent, pointer);
+    let error_message = Self::format_error_message(message, indent, Self::expand_tabs(&line[..column]).width(), line_index);
+    return format!("{}{}{}{}{}\n{}\n{}\n{}", prefix, color, span, Fg(Reset), suffix, pointers, pointer_line, error_message);
+  }
+
+  fn expand_tabs(text: &str) -> String {
+    return text.replace('\t', &" ".repeat(TAB_WIDTH));
+  }
 
-    let indent = cmp::min(PREFIX_LENGTH, column);
-    let (pointers, pointer_line) = Self::format_pointer(diagnostic, indent);
-    let error_message = Self::format_error_message(diagnostic, indent, column, line_index);
-    return format!("{}{}{}{}{}\n{}\n{}\n{}", prefix, Fg(Red), span, Fg(Reset), suffix, pointers, pointer_line, error_message);
+  fn color(diagnostic: &Diagnostic) -> &'static str {
+    return match diagnostic.kind {
+      DiagnosticKind::Error => Red.fg_str(),
+      DiagnosticKind::Warning => Yellow.fg_str()
+    };
   }
 
-  fn format_error_message(diagnostic: &Diagnostic, indent: usize, column: usize, line_index: usize) -> String {
-    return format!("{:indent$}+-- {} (Ln:{}, Col:{})", "", diagnostic.message, line_index + 1, column + 1, indent = indent);
+  fn format_error_message(message: &str, indent: usize, column: usize, line_index: usize) -> String {
+    return format!("{:indent$}+-- {} (Ln:{}, Col:{})", "", message, line_index + 1, column + 1, indent = indent);
   }
 
-  fn format_pointer(diagnostic: &Diagnostic, indent: usize) -> (String, String) {
-    let pointers = format!("{:indent$}{}", "", std::iter::repeat('^')
-      .take(
-        diagnostic.span.length()
-      )
-      .collect::<String>(), indent = indent);
+  fn format_pointer(span: &str, indent: usize, pointer: char) -> (String, String) {
+    let pointers = format!("{:indent$}{}", "", pointer.to_string().repeat(cmp::max(span.width(), 1)), indent = indent);
     let pointer_line = format!("{:indent$}|", "", indent = indent);
     return (pointers, pointer_line);
   }
 
-  fn text_spans(&'a self, diagnostic: &Diagnostic, line: &'a str, column: usize) -> (&'a str, &'a str, &'a str) {
-    let prefix_start = cmp::max(0, column as isize - PREFIX_LENGTH as isize) as usize;
+  fn text_spans(&'a self, text_span: &TextSpan, line: &'a str, column: usize) -> (&'a str, &'a str, &'a str) {
+    let prefix_start = line[..column].char_indices().rev().nth(PREFIX_LENGTH - 1).map_or(0, |(index, _)| index);
     let prefix_end = column;
-    let suffix_start = cmp::min(column + diagnostic.span.length(), line.len());
-    let suffix_end = cmp::min(suffix_start + PREFIX_LENGTH, line.len());
+    let suffix_start = cmp::min(column + text_span.length(), line.len());
+    let suffix_end = line[suffix_start..].char_indices().nth(PREFIX_LENGTH).map_or(line.len(), |(index, _)| suffix_start + index);
 
     let prefix = &line[prefix_start..prefix_end];
     let span = &line[prefix_end..suffix_start];
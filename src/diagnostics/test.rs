@@ -3,10 +3,12 @@ mod test {
   use crate::{
     compilation_unit::CompilationUnit, 
     diagnostics::{
+      printer::DiagnosticPrinter,
       Diagnostic,
       DiagnosticKind
     },
-    syntax::lexer::TextSpan
+    syntax::{lexer::TextSpan, parser::Parser, value::Value},
+    text::SourceText
   };
 
   struct DiagnosticVerifier {
@@ -24,6 +26,10 @@ mod test {
     fn compile(input: &str) -> Vec<Diagnostic> {
       let raw_text = Self::get_raw_text(input);
       let compilation_unit = CompilationUnit::compile(&raw_text);
+      // Runtime diagnostics are only produced for programs that compiled cleanly
+      if compilation_unit.diagnostic_glossary.borrow().diagnostics.is_empty() {
+        compilation_unit.evaluate();
+      }
       let diagnostics = compilation_unit.diagnostic_glossary.borrow();
       return diagnostics.diagnostics.clone();
     }
@@ -53,7 +59,7 @@ mod test {
             diagnostics.push(diagnostic);
           },
           _ => {
-            current_position += 1;
+            current_position += c.len_utf8();
           }
         }        
       }
@@ -73,6 +79,16 @@ mod test {
 
   }
 
+  // Renders the first diagnostic of the input and returns the pointer and message lines
+  fn render_pointer(input: &str) -> (String, String) {
+    let diagnostics = DiagnosticVerifier::compile(input);
+    let text = SourceText::new(input.to_string());
+    let printer = DiagnosticPrinter::new(&text, &diagnostics);
+    let rendered = printer.stringify_diagnostics(&diagnostics[0]);
+    let lines: Vec<&str> = rendered.lines().collect();
+    return (lines[1].to_string(), lines[3].to_string());
+  }
+
   #[test]
   fn should_report_undeclared_variable() {
     let input = "let a = <b>";
@@ -84,6 +100,461 @@ mod test {
     verifier.verify();
   }
 
+  #[test]
+  fn should_report_variables_declared_in_a_branch() {
+    let input = "if true { let a = 1 } else { let b = 2 }\nlet c = <a> + <b>";
+    let expected = vec![
+      "Variable 'a' is not in scope here",
+      "Variable 'b' is not in scope here"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_report_non_boolean_if_condition() {
+    let input = "let a = if <1 + 1> { 2 }";
+    let expected = vec![
+      "Expected a boolean condition, found integer"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_report_break_and_continue_outside_of_a_loop() {
+    let input = "<break>\nwhile true { if true { break } else { continue } }\nif true { <continue> }";
+    let expected = vec![
+      "'break' outside of a loop",
+      "'continue' outside of a loop"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_warn_about_statically_empty_ranges() {
+    let input = "for i in <10..0> { }\nfor i in 10..0 step -1 { }\nfor i in <(1)..=-(1)> { }";
+    let expected = vec![
+      "Range '10..0' is empty, the loop never runs",
+      "Range '(1)..=-(1)' is empty, the loop never runs"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_scope_loop_variables_to_the_loop() {
+    let input = "for i in 0..3 { i }\nlet a = <i>";
+    let expected = vec![
+      "Variable 'i' is not in scope here"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_report_invalid_iterables_and_steps() {
+    let input = "for i in <true> { }";
+    let expected = vec![
+      "Expected a range, an array or a map to iterate over, found boolean"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "let a = 0..10 <step> 0";
+    let expected = vec![
+      "Range step cannot be zero"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+  }
+
+  #[test]
+  fn should_point_at_the_declaration_of_an_out_of_scope_variable() {
+    let input = "let b = {\n  let a = 1\n  { let a = 2 }\n  a\n}\nlet c = <a>";
+    let expected = vec![
+      "Variable 'a' is not in scope here"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let diagnostics = DiagnosticVerifier::compile(&DiagnosticVerifier::get_raw_text(input));
+    let labels = &diagnostics[0].labels;
+    assert_eq!(labels.len(), 1);
+    assert_eq!(labels[0].message, "'a' is declared here, in a scope that has ended");
+    assert_eq!((labels[0].span.start, labels[0].span.end), (16, 17));
+  }
+
+  #[test]
+  fn should_render_labels_below_the_diagnostic() {
+    let input = "{ let a = 1 }\na";
+    let diagnostics = DiagnosticVerifier::compile(input);
+    let text = SourceText::new(input.to_string());
+    let printer = DiagnosticPrinter::new(&text, &diagnostics);
+    let rendered = printer.stringify_diagnostics(&diagnostics[0]);
+    let lines: Vec<&str> = rendered.lines().collect();
+    assert_eq!(lines.len(), 8);
+    assert_eq!(lines[5], "      -");
+    assert_eq!(lines[7], "      +-- 'a' is declared here, in a scope that has ended (Ln:1, Col:7)");
+  }
+
+  #[test]
+  fn should_report_wrong_argument_counts() {
+    let input = "fn add(a, b) { a + b }\nlet c = <add>(1)\nlet d = <add>(1, 2, 3)\nfn one(a) { a }\n<one>()";
+    let expected = vec![
+      "Function 'add' takes 2 arguments but 1 was given",
+      "Function 'add' takes 2 arguments but 3 were given",
+      "Function 'one' takes 1 argument but 0 were given"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let diagnostics = DiagnosticVerifier::compile(&DiagnosticVerifier::get_raw_text(input));
+    assert_eq!(diagnostics[0].labels[0].message, "'add' is declared here");
+    assert_eq!((diagnostics[0].labels[0].span.start, diagnostics[0].labels[0].span.end), (3, 6));
+
+    let input = "fn add(a, b) { a + b }\nlet f = add\nlet c = <f>(1)";
+    let expected = vec![
+      "Function 'add' takes 2 arguments but 1 was given"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+  }
+
+  #[test]
+  fn should_report_invalid_returns_and_calls() {
+    let input = "<return> 1\nfn f(a, <a>) { a }\nfn g() { <break> }";
+    let expected = vec![
+      "'return' outside of a function",
+      "Duplicate parameter 'a'",
+      "'break' outside of a loop"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "let a = 1\nlet b = <a>(2)";
+    let expected = vec![
+      "Cannot call a value of type integer"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+  }
+
+  #[test]
+  fn should_keep_function_locals_to_the_call() {
+    let input = "fn f() { let x = 1 }\nlet y = <x>";
+    let expected = vec![
+      "Variable 'x' is not in scope here"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+  }
+
+  #[test]
+  fn should_report_functions_outside_of_the_top_level() {
+    let input = "fn outer() {\n  fn <even>(n) { if n == 0 { true } else { odd(n - 1) } }\n  fn <odd>(n) { if n == 0 { false } else { even(n - 1) } }\n  even(4)\n}";
+    let expected = vec![
+      "Function 'even' must be declared at the top level",
+      "Function 'odd' must be declared at the top level"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "if true { fn <f>() { 1 } }\nlet g = || { fn <h>() { 2 } }";
+    let expected = vec![
+      "Function 'f' must be declared at the top level",
+      "Function 'h' must be declared at the top level"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+  }
+
+  #[test]
+  fn should_report_assignments_to_captured_variables() {
+    let input = "{\n  let mut count = 0\n  let bump = || { <count> += 1 }\n  let read = || count\n}";
+    let expected = vec![
+      "Cannot assign to 'count', closures capture variables by value"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let diagnostics = DiagnosticVerifier::compile(&DiagnosticVerifier::get_raw_text(input));
+    assert_eq!(diagnostics[0].labels[0].message, "'count' is declared here, outside of the closure");
+
+    let input = "let mut total = 0\nlet add = |x| { <total> += x }";
+    let expected = vec![
+      "Cannot assign to 'total', closures capture variables by value"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+  }
+
+  #[test]
+  fn should_check_closure_calls() {
+    let input = "let add = |a, b| a + b\nlet c = <add>(1)";
+    let expected = vec![
+      "Function 'add' takes 2 arguments but 1 was given"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "fn apply(f) { <f>(1, 2) }\nlet c = apply(|x| x)";
+    let expected = vec![
+      "Closure takes 1 argument but 2 were given"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "let f = |x| { return x }\nwhile true { let g = || { <break> } }";
+    let expected = vec![
+      "'break' outside of a loop"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+  }
+
+  #[test]
+  fn should_report_stack_overflow_instead_of_crashing() {
+    let input = "fn f(n) { 1 + <f>(n + 1) }\nf(0)";
+    let expected = vec![
+      "Stack overflow in script, calls nest deeper than 10000"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let compilation_unit = CompilationUnit::compile("fn sum(n) { if n == 0 { 0 } else { n + sum(n - 1) } }\nsum(1000)");
+    assert_eq!(compilation_unit.evaluate(), Some(Value::Integer(500500)));
+
+    let compilation_unit = CompilationUnit::compile("fn sum(n) { if n == 0 { 0 } else { n + sum(n - 1) } }\nsum(10)");
+    assert_eq!(compilation_unit.evaluate_with_max_call_depth(11), Some(Value::Integer(55)));
+    assert_eq!(compilation_unit.evaluate_with_max_call_depth(10), None);
+    let diagnostics = compilation_unit.diagnostic_glossary.borrow();
+    assert_eq!(diagnostics.diagnostics[0].message, "Stack overflow in script, calls nest deeper than 10");
+  }
+
+  #[test]
+  fn should_report_deeply_nested_recursion_instead_of_crashing() {
+    // Every call is nested in 30 levels of if, block and parentheses
+    let mut nested = "f(n - 1)".to_string();
+    for _ in 0..30 {
+      nested = format!("if true {{ {{ ( {} ) }} }}", nested);
+    }
+    let input = format!("fn f(n) {{ if n == 0 {{ 0 }} else {{ 1 + {} }} }}", nested);
+    let compilation_unit = CompilationUnit::compile(&format!("{}\nf(250)", input));
+    assert_eq!(compilation_unit.evaluate(), Some(Value::Integer(250)));
+
+    let compilation_unit = CompilationUnit::compile(&format!("{}\nf(5000)", input));
+    assert_eq!(compilation_unit.evaluate(), None);
+    let diagnostics = compilation_unit.diagnostic_glossary.borrow();
+    assert_eq!(diagnostics.diagnostics.len(), 1);
+    assert_eq!(diagnostics.diagnostics[0].message, "Stack overflow in script, expressions nest deeper than 100000");
+  }
+
+  #[test]
+  fn should_report_expressions_nested_too_deep_to_parse() {
+    // The initialiser is one level, each negation nests another
+    let negations = "-".repeat(Parser::MAX_DEPTH - 1);
+    let compilation_unit = CompilationUnit::compile(&format!("let a = {}1\na", negations));
+    assert!(compilation_unit.diagnostic_glossary.borrow().diagnostics.is_empty());
+    assert_eq!(compilation_unit.evaluate(), Some(Value::Integer(-1)));
+
+    let input = format!("let a = -{}<1>\nlet b = )", negations);
+    let expected = vec![
+      "Expressions nest deeper than 1000, the rest of the input is skipped"
+    ];
+    DiagnosticVerifier::new(&input, expected).verify();
+  }
+
+  #[test]
+  fn should_report_out_of_bounds_indexes() {
+    let input = "let xs = [1, 2, 3]\nlet a = xs[<7>]";
+    let expected = vec![
+      "Index 7 out of bounds for length 3"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "let xs = [1, 2, 3]\nlet a = xs[<-4>]";
+    let expected = vec![
+      "Index -4 out of bounds for length 3"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "let mut xs = [1, 2, 3]\nxs[<1 + 2>] = 0";
+    let expected = vec![
+      "Index 3 out of bounds for length 3"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "let xs = [1, 2, 3]\nlet a = xs[<1..5>]";
+    let expected = vec![
+      "Slice 1..5 out of bounds for length 3"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+  }
+
+  #[test]
+  fn should_report_missing_keys() {
+    let input = "let m = { \"a\": 1 }\nlet b = m[<\"b\">]";
+    let expected = vec![
+      "Key \"b\" not found in map"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "let mut m = { 1: 1 }\nm[<2>] += 1";
+    let expected = vec![
+      "Key 2 not found in map"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "let m = { <1.5>: 1 }";
+    let expected = vec![
+      "Cannot use a value of type float as a map key"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "let k = keys(<[1]>)";
+    let expected = vec![
+      "keys() expects a map, found array"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+  }
+
+  #[test]
+  fn should_report_invalid_indexing() {
+    let input = "let a = <1>[0]\n";
+    let expected = vec![
+      "Cannot index a value of type integer"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "let xs = [1]\nlet a = xs[<\"0\">]";
+    let expected = vec![
+      "Expected an integer or a range to index with, found string"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "let a = len(<1>)";
+    let expected = vec![
+      "len() expects an array, a map or a string, found integer"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "let xs = [1]\nlet a = <len>(xs, xs)\n<xs>[0] = 2\n<len> = 1";
+    let expected = vec![
+      "Function 'len' takes 1 argument but 2 were given",
+      "Cannot assign to an element of immutable variable 'xs'",
+      "Cannot assign to builtin function 'len'"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+  }
+
+  #[test]
+  fn should_report_unknown_fields_and_missing_initialisers() {
+    let input = "struct Point { x, y }\nlet p = Point { x: 1, y: 2, <z>: 3 }";
+    let expected = vec![
+      "Struct 'Point' has no field 'z'"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let diagnostics = DiagnosticVerifier::compile(&DiagnosticVerifier::get_raw_text(input));
+    assert_eq!(diagnostics[0].labels[0].message, "'Point' is declared here");
+    assert_eq!((diagnostics[0].labels[0].span.start, diagnostics[0].labels[0].span.end), (7, 12));
+
+    let input = "struct Point { x, y }\nlet p = <Point> { y: 2 }";
+    let expected = vec![
+      "Missing field 'x' in initialiser of struct 'Point'"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "struct Point { x, y, z }\nlet p = <Point> {}";
+    let expected = vec![
+      "Missing fields 'x', 'y', 'z' in initialiser of struct 'Point'"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "struct Point { x }\nlet p = Point { x: 1, <x>: 2 }";
+    let expected = vec![
+      "Field 'x' is initialised twice"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+  }
+
+  #[test]
+  fn should_report_invalid_struct_declarations() {
+    let input = "struct Point { x, <x> }\nstruct <Point> { y }\nfn f() { struct <Inner> { a } }\nlet p = <Line> { a: 1 }";
+    let expected = vec![
+      "Duplicate field 'x' in struct 'Point'",
+      "Struct 'Point' is already declared",
+      "Struct 'Inner' must be declared at the top level",
+      "Undeclared struct 'Line'"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "let p = <Point> { x: 1 }\nstruct Point { x }";
+    let expected = vec![
+      "Undeclared struct 'Point'"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+  }
+
+  #[test]
+  fn should_report_invalid_field_access() {
+    let input = "struct Point { x }\nlet p = Point { x: 1 }\nlet a = p.<y>";
+    let expected = vec![
+      "No struct declared so far has a field 'y'"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "struct Point { x }\nlet a = 1\nlet b = a.<x>";
+    let expected = vec![
+      "Cannot access field 'x' on a value of type integer"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "struct Point { x }\nstruct Size { w }\nlet p = Point { x: 1 }\nlet w = p.<w>";
+    let expected = vec![
+      "Struct 'Point' has no field 'w'"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "struct Point { x }\nstruct Size { w }\nlet mut p = Point { x: 1 }\np.<w> = 2";
+    let expected = vec![
+      "Struct 'Point' has no field 'w'"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "struct Point { x }\nlet p = Point { x: 1 }\n<p>.x = 2";
+    let expected = vec![
+      "Cannot assign to a field of immutable variable 'p'"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+  }
+
+  #[test]
+  fn should_report_assignment_to_immutable_variables() {
+    let input = "let a = 1\n<a> = 2\nfor i in 0..3 { <i> += 1 }\nlet mut b = 1\nb = 2";
+    let expected = vec![
+      "Cannot assign twice to immutable variable 'a'",
+      "Cannot assign twice to immutable variable 'i'"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let diagnostics = DiagnosticVerifier::compile(&DiagnosticVerifier::get_raw_text(input));
+    assert_eq!(diagnostics[0].labels[0].message, "'a' is declared here, 'let mut' would make it mutable");
+    assert_eq!((diagnostics[0].labels[0].span.start, diagnostics[0].labels[0].span.end), (4, 5));
+  }
+
+  #[test]
+  fn should_report_invalid_assignments() {
+    let input = "let mut a = 1\n<a + 1> = 2";
+    let expected = vec![
+      "Invalid assignment target, only a variable, an element or a field can be assigned to"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "<c> = 3";
+    let expected = vec![
+      "Undeclared variable 'c'"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "let mut a = \"x\"\na <-=> 1";
+    let expected = vec![
+      "Unsupported operand types for '-=': string and integer"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+  }
+
   #[test]
   fn should_report_expected_expression() {
     let input = "let a = <+>";
@@ -98,10 +569,307 @@ mod test {
   fn should_report_bad_token() {
     let input = "let a = 8 <@> 2";
     let expected = vec![
-      "Expected expression, found <Bad>"
+      "Unexpected character '@' (U+0040)"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_report_division_by_zero() {
+    let input = "let a = 1 </> 0";
+    let expected = vec![
+      "Division by zero"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_report_integer_overflow() {
+    let input = "let a = 9223372036854775807 <+> 1";
+    let expected = vec![
+      "Integer overflow"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  // `<` and `>` mark spans in the verifier input, so shifts are checked directly
+  #[test]
+  fn should_report_shift_out_of_range_and_overflow() {
+    for (input, message) in [("let a = 1 << 64", "Shift amount 64 out of range"), ("let a = 3 << 62", "Integer overflow")] {
+      let compilation_unit = CompilationUnit::compile(input);
+      compilation_unit.evaluate();
+      let diagnostics = compilation_unit.diagnostic_glossary.borrow();
+      let diagnostics = &diagnostics.diagnostics;
+      assert_eq!(diagnostics.len(), 1, "Expected 1 diagnostic, found {:?} instead", diagnostics);
+      assert_eq!(diagnostics[0].message, message);
+      assert_eq!((diagnostics[0].span.start, diagnostics[0].span.literal.as_str()), (10, "<<"));
+    }
+  }
+
+  #[test]
+  fn should_report_non_boolean_condition() {
+    let input = "let a = true && <1 + 1>";
+    let expected = vec![
+      "Expected a boolean condition, found integer"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_stop_at_a_non_boolean_condition() {
+    let compilation_unit = CompilationUnit::compile("let a = !1\nlet b = 2");
+    assert_eq!(compilation_unit.evaluate(), None);
+    assert_eq!(compilation_unit.diagnostic_glossary.borrow().diagnostics.len(), 1);
+  }
+
+  #[test]
+  fn should_report_ordering_of_booleans() {
+    let compilation_unit = CompilationUnit::compile("let a = true > false");
+    compilation_unit.evaluate();
+    let diagnostics = compilation_unit.diagnostic_glossary.borrow();
+    let diagnostics = &diagnostics.diagnostics;
+    assert_eq!(diagnostics.len(), 1, "Expected 1 diagnostic, found {:?} instead", diagnostics);
+    assert_eq!(diagnostics[0].message, "Unsupported operand types for '>': boolean and boolean");
+  }
+
+  #[test]
+  fn should_report_unterminated_string() {
+    let input = r#"let a = <">abc"#;
+    let expected = vec![
+      "Unterminated string literal"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_report_unknown_escape_sequence() {
+    let input = r#"let a = "a<\q>b""#;
+    let expected = vec![
+      r"Unknown escape sequence '\q'"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_report_unterminated_interpolation() {
+    let input = r#"let a = "<${>1 + 2"#;
+    let expected = vec![
+      "Unterminated string interpolation"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_report_unsupported_string_operands() {
+    let input = r#"let a = "a" <-> "b""#;
+    let expected = vec![
+      "Unsupported operand types for '-': string and string"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_report_unterminated_block_comment() {
+    let input = "let a = 1 </*> /* */";
+    let expected = vec![
+      "Unterminated block comment"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_report_mixed_script_identifier() {
+    let input = "let <pаy> = 1";
+    let expected = vec![
+      "Identifier 'pаy' mixes Latin and Cyrillic scripts"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_report_confusable_identifier() {
+    let input = "let cop = 1\nlet <сор> = 2\nfn f(<сор>) { cop }";
+    let expected = vec![
+      "Identifier 'сор' is confusable with 'cop'",
+      "Identifier 'сор' is confusable with 'cop'"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_only_report_identifiers_confusable_with_one_another() {
+    let input = "let α = 1\nlet ρ = α * 2\nlet сор = ρ\nfn τ(ν) { ν }";
+    let expected = vec![];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+
+    // Functions are declared ahead of the program
+    let input = "let <p> = ρ()\nfn ρ() { 1 }\np + ρ()";
+    let expected = vec![
+      "Identifier 'p' is confusable with 'ρ'"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_report_spans_as_byte_offsets() {
+    let input = "let π = 3 /* 🎉 */\nlet é = <b>";
+    let expected = vec![
+      "Undeclared variable 'b'"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_align_pointers_by_display_width() {
+    let (pointers, message) = render_pointer("let π = b");
+    assert_eq!(pointers, "        ^");
+    assert_eq!(message, "        +-- Undeclared variable 'b' (Ln:1, Col:9)");
+
+    let (pointers, message) = render_pointer("/*🎉*/ 1+b");
+    assert_eq!(pointers, "         ^");
+    assert_eq!(message, "         +-- Undeclared variable 'b' (Ln:1, Col:10)");
+
+    let (pointers, message) = render_pointer("let 名前 = b");
+    assert_eq!(pointers, "          ^");
+    assert_eq!(message, "          +-- Undeclared variable 'b' (Ln:1, Col:12)");
+  }
+
+  #[test]
+  fn should_expand_tabs_before_pointers() {
+    let input = "\t1+b";
+    let diagnostics = DiagnosticVerifier::compile(input);
+    let text = SourceText::new(input.to_string());
+    let rendered = DiagnosticPrinter::new(&text, &diagnostics).stringify_diagnostics(&diagnostics[0]);
+    let lines: Vec<&str> = rendered.lines().collect();
+    assert!(lines[0].starts_with("    1+"), "Unexpected source line {:?}", lines[0]);
+    assert_eq!(lines[1], "      ^");
+    assert_eq!(lines[3], "      +-- Undeclared variable 'b' (Ln:1, Col:7)");
+  }
+
+  #[test]
+  fn should_point_at_the_start_of_a_line() {
+    let (pointers, message) = render_pointer("let a = 1\nb");
+    assert_eq!(pointers, "^");
+    assert_eq!(message, "+-- Undeclared variable 'b' (Ln:2, Col:1)");
+  }
+
+  #[test]
+  fn should_report_integer_literal_out_of_range() {
+    let input = "let a = <99999999999999999999> + <0xFFFFFFFFFFFFFFFFF>\nlet b = -<1e999> * a";
+    let expected = vec![
+      "Integer literal '99999999999999999999' out of range, add the 'n' suffix for an arbitrary-precision integer",
+      "Integer literal '0xFFFFFFFFFFFFFFFFF' out of range, add the 'n' suffix for an arbitrary-precision integer",
+      "Float literal '1e999' out of range"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_report_juxtaposed_expressions() {
+    let input = "let a = 1 <let> b = 2\n1 <2>";
+    let expected = vec![
+      "Expected end of statement, found <Let>",
+      "Expected end of statement, found <Number>"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_report_every_independent_syntax_error() {
+    let input = "let a = (1 + 2\n<let> <=> 3\nlet c = 4 + <;>\n1 <)>\nlet d = (<>";
+    let expected = vec![
+      "Expected <)>, found <Let>",
+      "Expected <Identifier>, found <=>",
+      "Expected expression, found <;>",
+      "Expected end of statement, found <)>",
+      "Expected expression, found <EOF>"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_not_report_errors_caused_by_an_earlier_error() {
+    let input = "let <5>\nlet b = (1 + <)> + (2 * <)>";
+    let expected = vec![
+      "Expected <Identifier>, found <Number>",
+      "Expected expression, found <)>",
+      "Expected expression, found <)>"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_skip_brackets_opened_after_an_error_up_to_where_they_close() {
+    let input = "foo <bar> { 1\n 2 }";
+    let expected = vec![
+      "Expected end of statement, found <Identifier>"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+
+    let input = "if true {\n1\n}\n<else> {\n2\n}";
+    let expected = vec![
+      "Expected expression, found <Else>"
+    ];
+    DiagnosticVerifier::new(input, expected).verify();
+  }
+
+  #[test]
+  fn should_group_runs_of_bad_characters() {
+    let input = "let a = 1 <@#$> + 2";
+    let expected = vec![
+      "Unexpected characters '@#$'"
+    ];
+
+    let verifier = DiagnosticVerifier::new(input, expected);
+    verifier.verify();
+  }
+
+  #[test]
+  fn should_hint_at_lookalike_characters() {
+    let input = "let a = <“>hi<”>\nlet b = 1 <≠> 2";
+    let expected = vec![
+      "Unexpected character '“' (U+201C), did you mean '\"'?",
+      "Unexpected character '”' (U+201D), did you mean '\"'?",
+      "Unexpected character '≠' (U+2260), did you mean '!='?"
     ];
 
     let verifier = DiagnosticVerifier::new(input, expected);
     verifier.verify();
   }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,10 @@
+// Explicit `return` is the house style, and the tests live in a `test` module
+// of their own `test.rs`.
+#![allow(clippy::needless_return, clippy::module_inception)]
+
+pub mod syntax;
+pub mod diagnostics;
+pub mod text;
+pub mod compilation_unit;
+mod symbols;
+mod structs;
@@ -1,14 +1,8 @@
-use compilation_unit::CompilationUnit;
+use unilang::compilation_unit::CompilationUnit;
 
 // use termion::{raw::IntoRawMode, input::TermRead, event::Key};
 // use std::{fmt::write, io::{self, Read, Write}};
 
-mod syntax;
-mod diagnostics;
-mod text;
-mod symbols;
-mod compilation_unit;
-
 fn main() {
 	// let input = "\
 	// 	let a = 10+30
@@ -0,0 +1,124 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+  diagnostics::DiagnosticGlossaryCell,
+  syntax::{
+    lexer::{TextSpan, Token},
+    types::{
+      _block::BlockExpression,
+      _let::LetStatement,
+      _number::NumberExpression,
+      _struct::{FieldExpression, StructDeclaration, StructLiteralExpression},
+      _unary::UnaryExpression,
+      _variable::VariableExpression
+    },
+    visitor::SyntaxTreeVisitor
+  }
+};
+
+
+// Structs are declared at the top level, and like functions they can only be
+// used after their declaration. A literal has to initialise every field of its
+// struct and nothing else. What struct a value is only shows when it runs, so
+// a field access is only checked against the fields of every struct.
+pub struct StructChecker {
+  structs: HashMap<String, StructDeclaration>,
+  // Fields of every struct declared so far
+  fields: HashSet<String>,
+  // Number of blocks around the statement being checked
+  block_depth: usize,
+  diagnostics: DiagnosticGlossaryCell
+}
+
+impl StructChecker {
+  pub fn new(diagnostics: DiagnosticGlossaryCell) -> Self {
+    StructChecker {
+      structs: HashMap::new(),
+      fields: HashSet::new(),
+      block_depth: 0,
+      diagnostics
+    }
+  }
+}
+
+// The first of the tokens spelled like one before it
+fn find_duplicates<'a>(tokens: impl Iterator<Item = &'a Token>) -> Vec<&'a Token> {
+  let mut seen = HashSet::new();
+  return tokens.filter(|token| !seen.insert(&token.span.literal)).collect();
+}
+
+impl SyntaxTreeVisitor for StructChecker {
+  fn visit_let_statement(&mut self, let_statement: &LetStatement) {
+    self.visit_expression(&let_statement.initialiser);
+  }
+
+  fn visit_struct_declaration(&mut self, struct_declaration: &StructDeclaration) {
+    let identifier = &struct_declaration.identifier;
+    let mut diagnostics_binding = self.diagnostics.borrow_mut();
+    if self.block_depth > 0 {
+      diagnostics_binding.report_struct_outside_of_top_level(identifier);
+    }
+    for field in find_duplicates(struct_declaration.fields.iter()) {
+      diagnostics_binding.report_duplicate_field(field, &identifier.span.literal);
+    }
+    if let Some(declared) = self.structs.get(&identifier.span.literal) {
+      diagnostics_binding.report_duplicate_struct(identifier, &declared.identifier.span);
+      return;
+    }
+    self.fields.extend(struct_declaration.fields.iter().map(|field| field.span.literal.clone()));
+    self.structs.insert(identifier.span.literal.clone(), struct_declaration.clone());
+  }
+
+  fn visit_struct_literal_expression(&mut self, struct_literal_expression: &StructLiteralExpression) {
+    for initialiser in &struct_literal_expression.initialisers {
+      self.visit_expression(&initialiser.value);
+    }
+    let identifier = &struct_literal_expression.identifier;
+    let mut diagnostics_binding = self.diagnostics.borrow_mut();
+    let Some(declaration) = self.structs.get(&identifier.span.literal) else {
+      diagnostics_binding.report_undeclared_struct(identifier);
+      return;
+    };
+    let initialised = || struct_literal_expression.initialisers.iter().map(|initialiser| &initialiser.field);
+    for field in initialised() {
+      if !declaration.fields.iter().any(|declared| declared.span.literal == field.span.literal) {
+        diagnostics_binding.report_unknown_field(field, &identifier.span.literal, &declaration.identifier.span);
+      }
+    }
+    for field in find_duplicates(initialised()) {
+      diagnostics_binding.report_duplicate_initialiser(field);
+    }
+    let missing: Vec<&str> = declaration.fields.iter()
+      .map(|field| field.span.literal.as_str())
+      .filter(|field| !initialised().any(|initialised| initialised.span.literal == *field))
+      .collect();
+    if !missing.is_empty() {
+      diagnostics_binding.report_missing_initialisers(identifier, &missing, &declaration.identifier.span);
+    }
+  }
+
+  fn visit_field_expression(&mut self, field_expression: &FieldExpression) {
+    self.visit_expression(&field_expression.target);
+    if !self.fields.contains(&field_expression.field.span.literal) {
+      self.diagnostics.borrow_mut().report_field_of_no_struct(&field_expression.field);
+    }
+  }
+
+  fn visit_block_expression(&mut self, block_expression: &BlockExpression) {
+    self.block_depth += 1;
+    for statement in &block_expression.statements {
+      self.visit_statement(statement);
+    }
+    self.block_depth -= 1;
+  }
+
+  fn visit_number(&mut self, _number: &NumberExpression) {}
+
+  fn visit_variable_expression(&mut self, _variable_expression: &VariableExpression) {}
+
+  fn visit_error(&mut self, _span: &TextSpan) {}
+
+  fn visit_unary_expression(&mut self, unary_expression: &UnaryExpression) {
+    self.visit_expression(&unary_expression.operand);
+  }
+}
@@ -1,45 +1,350 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::{
   diagnostics::DiagnosticGlossaryCell, 
   syntax::{
-    lexer::TextSpan, types::{
+    builtins::Builtin,
+    identifier,
+    expression::{SyntaxTreeExpression, SyntaxTreeExpressionKind},
+    lexer::{TextSpan, Token},
+    operators::PostfixOperatorKind,
+    statement::{SyntaxTreeStatement, SyntaxTreeStatementKind},
+    types::{
+      _assignment::AssignmentExpression,
+      _binary::BinaryOperatorKind,
+      _block::BlockExpression,
+      _call::CallExpression,
+      _closure::ClosureExpression,
+      _for::ForStatement,
+      _function::FunctionDeclaration,
       _let::LetStatement,
-      _number::NumberExpression,
-      _unary::UnaryExpression,
-      _variable::VariableExpression
+      _number::{Number, NumberExpression},
+      _return::ReturnStatement,
+      _unary::{UnaryExpression, UnaryOperatorKind},
+      _variable::VariableExpression,
+      _while::WhileStatement
     }, 
+    value::RangeValue,
     visitor::SyntaxTreeVisitor
   }
 };
 
 
+pub struct Symbol {
+  // Identifier of the binding
+  pub declaration: TextSpan,
+  pub mutable: bool,
+  // Number of parameters of a declared function
+  pub arity: Option<usize>
+}
+
+// A function body is checked in a frame of its own, the closures around the
+// declaration don't capture for it
+struct Frame {
+  closures: Vec<usize>
+}
+
+// Every block opens a scope. A `let` binds a name in the innermost scope and
+// shadows any binding of the same name, in that scope or an enclosing one, for
+// the rest of the scope.
 pub struct SymbolChecker {
-  symbols: HashMap<String, ()>,
+  scopes: Vec<HashMap<String, Symbol>>,
+  // Last binding of each name in a scope that has ended, to point at it when
+  // the name is used outside of it
+  ended: HashMap<String, Symbol>,
+  frames: Vec<Frame>,
+  // Number of scopes around each closure being checked. A closure sees every
+  // scope around it as a copy, the global scope included.
+  closures: Vec<usize>,
+  // Number of loops around the statement being checked, in the function being checked
+  loop_depth: usize,
+  // First declaration of a name with each skeleton, anywhere in the program
+  skeletons: HashMap<String, TextSpan>,
+  // Declarations checked against the skeletons already, functions are
+  // declared ahead of the program and again where they are
+  checked: HashSet<usize>,
   diagnostics: DiagnosticGlossaryCell
 }
 
 impl SymbolChecker {
   pub fn new(diagnostics: DiagnosticGlossaryCell) -> Self {
     SymbolChecker {
-      symbols: HashMap::new(),
+      scopes: vec![HashMap::new()],
+      ended: HashMap::new(),
+      frames: Vec::new(),
+      closures: Vec::new(),
+      loop_depth: 0,
+      skeletons: HashMap::new(),
+      checked: HashSet::new(),
       diagnostics
     }
   }
+
+  fn begin_scope(&mut self) {
+    self.scopes.push(HashMap::new());
+  }
+
+  fn end_scope(&mut self) {
+    let scope = self.scopes.pop().unwrap();
+    self.ended.extend(scope);
+  }
+
+  fn declare(&mut self, identifier: &Token, mutable: bool) {
+    self.declare_symbol(identifier, Symbol { declaration: identifier.span.clone(), mutable, arity: None });
+  }
+
+  fn declare_symbol(&mut self, identifier: &Token, symbol: Symbol) {
+    self.check_confusable(identifier);
+    self.scopes.last_mut().unwrap().insert(identifier.span.literal.clone(), symbol);
+  }
+
+  // A name spelled with lookalikes of the letters of another name declared in
+  // the program is almost always a mistake or a spoofing attempt. A name with
+  // lookalikes of no other name is fine, it is just spelled in its own script.
+  fn check_confusable(&mut self, identifier: &Token) {
+    if !self.checked.insert(identifier.span.start) {
+      return;
+    }
+    let skeleton = identifier::skeleton(&identifier.span.literal);
+    match self.skeletons.get(&skeleton) {
+      Some(declaration) if declaration.literal != identifier.span.literal => {
+        self.diagnostics.borrow_mut().report_confusable_identifier(identifier, declaration);
+      }
+      Some(_) => {}
+      None => {
+        self.skeletons.insert(skeleton, identifier.span.clone());
+      }
+    }
+  }
+
+  fn lookup(&self, name: &str) -> Option<&Symbol> {
+    return self.scopes.iter().rev().find_map(|scope| scope.get(name));
+  }
+
+  // Whether the innermost closure has its own copy of the binding of a name
+  fn is_captured(&self, name: &str) -> bool {
+    let Some(&boundary) = self.closures.last() else {
+      return false;
+    };
+    let scope = self.scopes.iter().rposition(|scope| scope.contains_key(name));
+    return scope.is_some_and(|scope| scope < boundary);
+  }
+
+  fn function_symbol(function_declaration: &FunctionDeclaration) -> Symbol {
+    let declaration = function_declaration.identifier.span.clone();
+    return Symbol { declaration, mutable: false, arity: Some(function_declaration.parameters.len()) };
+  }
+
+  // A function can be called anywhere in the block or program it is declared
+  // in, before its declaration too, so functions can call each other
+  pub fn declare_functions(&mut self, statements: &[SyntaxTreeStatement]) {
+    for statement in statements {
+      if let SyntaxTreeStatementKind::Function(function_declaration) = &statement.kind {
+        self.declare_symbol(&function_declaration.identifier, Self::function_symbol(function_declaration));
+      }
+    }
+  }
+
+  fn declare_parameters(&mut self, parameters: &[Token]) {
+    for (index, parameter) in parameters.iter().enumerate() {
+      if parameters[..index].iter().any(|other| other.span.literal == parameter.span.literal) {
+        self.diagnostics.borrow_mut().report_duplicate_parameter(parameter);
+      }
+      self.declare(parameter, false);
+    }
+  }
+
+  // Reports a variable that isn't bound here, returns its symbol otherwise.
+  // A builtin has no symbol, but isn't reported.
+  fn resolve(&self, identifier: &Token) -> Option<&Symbol> {
+    let name = &identifier.span.literal;
+    let symbol = self.lookup(name);
+    if symbol.is_none() && Builtin::lookup(name).is_none() {
+      let mut diagnostics_binding = self.diagnostics.borrow_mut();
+      match self.ended.get(name) {
+        Some(symbol) => diagnostics_binding.report_variable_out_of_scope(identifier, &symbol.declaration),
+        None => diagnostics_binding.report_undeclared_variable(identifier)
+      }
+    }
+    return symbol;
+  }
+}
+
+// Range of an iterable spelled out with integer literals, for the empty range lint
+fn constant_range(expression: &SyntaxTreeExpression) -> Option<RangeValue> {
+  let SyntaxTreeExpressionKind::Binary(binary) = &expression.kind else {
+    return None;
+  };
+  return match binary.operator.kind {
+    BinaryOperatorKind::Range | BinaryOperatorKind::RangeInclusive => Some(RangeValue::new(
+      constant_integer(&binary.left)?,
+      constant_integer(&binary.right)?,
+      binary.operator.kind == BinaryOperatorKind::RangeInclusive
+    )),
+    BinaryOperatorKind::Step => match constant_integer(&binary.right)? {
+      0 => None,
+      step => Some(RangeValue { step, ..constant_range(&binary.left)? })
+    },
+    _ => None
+  };
+}
+
+fn constant_integer(expression: &SyntaxTreeExpression) -> Option<i64> {
+  return match &expression.kind {
+    SyntaxTreeExpressionKind::Number(NumberExpression { number: Number::Integer(integer) }) => Some(*integer),
+    SyntaxTreeExpressionKind::Unary(unary) if unary.operator.kind == UnaryOperatorKind::Minus => constant_integer(&unary.operand)?.checked_neg(),
+    SyntaxTreeExpressionKind::Parenthesised(parenthesised) => constant_integer(&parenthesised.expression),
+    _ => None
+  };
 }
 
 impl SyntaxTreeVisitor for SymbolChecker {
+  // A closure bound to an immutable variable has a known arity, like a declared function
   fn visit_let_statement(&mut self, let_statement: &LetStatement) {
-    let identifier = let_statement.identifier.span.literal.clone();
     self.visit_expression(&let_statement.initialiser);
-    self.symbols.insert(identifier, ());
+    let arity = match &let_statement.initialiser.kind {
+      SyntaxTreeExpressionKind::Closure(closure) if !let_statement.mutable => Some(closure.parameters.len()),
+      _ => None
+    };
+    let identifier = &let_statement.identifier;
+    self.declare_symbol(identifier, Symbol { declaration: identifier.span.clone(), mutable: let_statement.mutable, arity });
   } 
 
+  // Functions are declared at the top level, so a function body only sees the
+  // globals, every function among them, and its own bindings. The function is
+  // bound in its own frame as well, so it can call itself.
+  fn visit_function_declaration(&mut self, function_declaration: &FunctionDeclaration) {
+    let identifier = &function_declaration.identifier;
+    if self.scopes.len() > 1 {
+      self.diagnostics.borrow_mut().report_function_outside_of_top_level(identifier);
+    }
+    self.declare_symbol(identifier, Self::function_symbol(function_declaration));
+
+    let closures = std::mem::take(&mut self.closures);
+    self.frames.push(Frame { closures });
+    let loop_depth = std::mem::replace(&mut self.loop_depth, 0);
+    self.begin_scope();
+    self.declare_symbol(identifier, Self::function_symbol(function_declaration));
+    self.declare_parameters(&function_declaration.parameters);
+    self.visit_expression(&function_declaration.body);
+    self.end_scope();
+    self.loop_depth = loop_depth;
+    self.closures = self.frames.pop().unwrap().closures;
+  }
+
+  fn visit_closure_expression(&mut self, closure_expression: &ClosureExpression) {
+    self.closures.push(self.scopes.len());
+    let loop_depth = std::mem::replace(&mut self.loop_depth, 0);
+    self.begin_scope();
+    self.declare_parameters(&closure_expression.parameters);
+    self.visit_expression(&closure_expression.body);
+    self.end_scope();
+    self.loop_depth = loop_depth;
+    self.closures.pop();
+  }
+
+  fn visit_return_statement(&mut self, return_statement: &ReturnStatement) {
+    if self.frames.is_empty() && self.closures.is_empty() {
+      self.diagnostics.borrow_mut().report_outside_of_function(&return_statement.keyword);
+    }
+    if let Some(value) = &return_statement.value {
+      self.visit_expression(value);
+    }
+  }
+
+  fn visit_call_expression(&mut self, call_expression: &CallExpression) {
+    self.visit_expression(&call_expression.callee);
+    for argument in &call_expression.arguments {
+      self.visit_expression(argument);
+    }
+    let SyntaxTreeExpressionKind::Variable(callee) = &call_expression.callee.kind else {
+      return;
+    };
+    let name = &callee.identifier.span.literal;
+    let (arity, declaration) = match self.lookup(name) {
+      Some(symbol) => (symbol.arity, Some(&symbol.declaration)),
+      None => (Builtin::lookup(name).map(|builtin| builtin.arity()), None)
+    };
+    if let Some(arity) = arity.filter(|arity| *arity != call_expression.arguments.len()) {
+      self.diagnostics.borrow_mut().report_wrong_argument_count(
+        call_expression.callee.span.clone(), Some(name), arity, call_expression.arguments.len(), declaration
+      );
+    }
+  }
+
+  fn visit_while_statement(&mut self, while_statement: &WhileStatement) {
+    self.visit_expression(&while_statement.condition);
+    self.loop_depth += 1;
+    self.visit_expression(&while_statement.body);
+    self.loop_depth -= 1;
+  }
+
+  fn visit_for_statement(&mut self, for_statement: &ForStatement) {
+    self.visit_expression(&for_statement.iterable);
+    if constant_range(&for_statement.iterable).is_some_and(|range| range.is_empty()) {
+      self.diagnostics.borrow_mut().report_empty_range(for_statement.iterable.span.clone());
+    }
+    self.begin_scope();
+    self.declare(&for_statement.identifier, false);
+    self.loop_depth += 1;
+    self.visit_expression(&for_statement.body);
+    self.loop_depth -= 1;
+    self.end_scope();
+  }
+
+  fn visit_break_statement(&mut self, keyword: &Token) {
+    if self.loop_depth == 0 {
+      self.diagnostics.borrow_mut().report_outside_of_loop(keyword);
+    }
+  }
+
+  fn visit_continue_statement(&mut self, keyword: &Token) {
+    if self.loop_depth == 0 {
+      self.diagnostics.borrow_mut().report_outside_of_loop(keyword);
+    }
+  }
+
   fn visit_variable_expression(&mut self, variable_expression: &VariableExpression) {
-    if self.symbols.get(&variable_expression.identifier.span.literal).is_none() {
+    self.resolve(&variable_expression.identifier);
+  }
+
+  // Assigning to an element of an array or a field of a struct changes the
+  // variable holding it. The outermost part of the target names what is assigned.
+  fn visit_assignment_expression(&mut self, assignment_expression: &AssignmentExpression) {
+    let mut target = &*assignment_expression.target;
+    let mut part = None;
+    loop {
+      target = match &target.kind {
+        SyntaxTreeExpressionKind::Index(index_expression) => {
+          self.visit_expression(&index_expression.index);
+          part.get_or_insert(PostfixOperatorKind::Index);
+          &index_expression.target
+        }
+        SyntaxTreeExpressionKind::Field(field_expression) => {
+          part.get_or_insert(PostfixOperatorKind::Field);
+          &field_expression.target
+        }
+        _ => break
+      };
+    }
+    if let SyntaxTreeExpressionKind::Variable(target) = &target.kind {
+      let identifier = &target.identifier;
+      let symbol = self.resolve(identifier);
       let mut diagnostics_binding = self.diagnostics.borrow_mut();
-      diagnostics_binding.report_undeclared_variable(&variable_expression.identifier);
+      match symbol {
+        Some(symbol) if !symbol.mutable && part == Some(PostfixOperatorKind::Index) => {
+          diagnostics_binding.report_assignment_to_element_of_immutable(identifier, &symbol.declaration)
+        }
+        Some(symbol) if !symbol.mutable && part == Some(PostfixOperatorKind::Field) => {
+          diagnostics_binding.report_assignment_to_field_of_immutable(identifier, &symbol.declaration)
+        }
+        Some(symbol) if !symbol.mutable => diagnostics_binding.report_assignment_to_immutable(identifier, &symbol.declaration),
+        Some(symbol) if self.is_captured(&identifier.span.literal) => diagnostics_binding.report_assignment_to_captured(identifier, &symbol.declaration),
+        None if Builtin::lookup(&identifier.span.literal).is_some() => diagnostics_binding.report_assignment_to_builtin(identifier),
+        _ => {}
+      }
     }
+    self.visit_expression(&assignment_expression.value);
   }
 
   fn visit_number(&mut self, number: &NumberExpression) {
@@ -54,6 +359,15 @@ impl SyntaxTreeVisitor for SymbolChecker {
     // TODO
   }
 
+  fn visit_block_expression(&mut self, block_expression: &BlockExpression) {
+    self.begin_scope();
+    self.declare_functions(&block_expression.statements);
+    for statement in &block_expression.statements {
+      self.visit_statement(statement);
+    }
+    self.end_scope();
+  }
+
   fn visit_unary_expression(&mut self, unary_expression: &UnaryExpression) {
     self.visit_expression(&unary_expression.operand);
   }
@@ -0,0 +1,33 @@
+// Functions every program can call without declaring them. A declaration of
+// the same name shadows a builtin like any other binding.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Builtin {
+  Len,
+  Keys,
+  Values,
+  Contains
+}
+
+impl Builtin {
+  const ALL: [Builtin; 4] = [Builtin::Len, Builtin::Keys, Builtin::Values, Builtin::Contains];
+
+  pub fn lookup(name: &str) -> Option<Builtin> {
+    return Self::ALL.iter().copied().find(|builtin| builtin.name() == name);
+  }
+
+  pub fn name(&self) -> &'static str {
+    return match self {
+      Builtin::Len => "len",
+      Builtin::Keys => "keys",
+      Builtin::Values => "values",
+      Builtin::Contains => "contains"
+    };
+  }
+
+  pub fn arity(&self) -> usize {
+    return match self {
+      Builtin::Len | Builtin::Keys | Builtin::Values => 1,
+      Builtin::Contains => 2
+    };
+  }
+}
@@ -1,79 +1,967 @@
-use std::collections::HashMap;
+use std::{cmp::Ordering, collections::HashMap, rc::Rc};
+
+use num_bigint::BigInt;
+use num_traits::{ToPrimitive, Zero};
+
+use crate::diagnostics::DiagnosticGlossaryCell;
 
 use super::{
-  lexer::TextSpan,
+  builtins::Builtin,
+  ensure_stack,
+  expression::{SyntaxTreeExpression, SyntaxTreeExpressionKind},
+  lexer::{TextSpan, Token},
+  statement::{SyntaxTreeStatement, SyntaxTreeStatementKind},
   types::{
+    _array::ArrayExpression,
+    _assignment::AssignmentExpression,
     _binary::{
       BinaryExpression,
       BinaryOperatorKind
     }, 
+    _block::BlockExpression,
+    _boolean::BooleanExpression,
+    _call::CallExpression,
+    _closure::ClosureExpression,
+    _for::ForStatement,
+    _function::FunctionDeclaration,
+    _if::IfExpression,
+    _index::IndexExpression,
+    _map::MapExpression,
     _let::LetStatement,
     _number::NumberExpression,
+    _return::ReturnStatement,
+    _struct::{FieldExpression, StructDeclaration, StructLiteralExpression},
+    _string::{
+      InterpolationExpression,
+      InterpolationPart,
+      StringExpression
+    },
     _unary::{
       UnaryExpression,
       UnaryOperatorKind
     },
-    _variable::VariableExpression
+    _variable::VariableExpression,
+    _while::WhileStatement
   }, 
+  value::{FunctionValue, MapKey, MapValue, RangeValue, StructValue, Value},
   visitor::SyntaxTreeVisitor
 };
 
 
+// A jump out of the statements being run, pending until the loop or call it
+// belongs to takes it
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ControlFlow {
+  Break,
+  Continue,
+  // The returned value is the last value
+  Return
+}
+
+// A step from a value to a part of it, an element or a field
+enum Accessor {
+  Index(Value, TextSpan),
+  Field(Token)
+}
+
+// A variable, or a part of the value held by one, with the steps on the way
+// to the part
+struct Place {
+  identifier: Token,
+  accessors: Vec<Accessor>
+}
+
 pub struct Evaluator {
-  pub last_value: Option<i64>,
-  pub variables: HashMap<String, i64>
+  pub last_value: Option<Value>,
+  // Innermost scope last, like the scopes of the SymbolChecker. The first one
+  // holds the globals, the others belong to the function being run.
+  pub scopes: Vec<HashMap<String, Value>>,
+  diagnostics: DiagnosticGlossaryCell,
+  // Set once a runtime error has been reported, every visit after that is a no-op
+  halted: bool,
+  // Statements and expressions are skipped while a jump is pending
+  control_flow: Option<ControlFlow>,
+  // Call made in tail position, run by the call it replaces once that one's
+  // body has been left
+  tail_call: Option<(Rc<FunctionValue>, Vec<Value>)>,
+  // Calls being run, tail calls don't add to it
+  call_depth: usize,
+  max_call_depth: usize,
+  // Expressions being visited, in every call being run. Each one takes up
+  // some host stack, however the script nests them.
+  depth: usize,
+  max_depth: usize,
+  // Fields of each struct declared so far, in declaration order
+  structs: HashMap<String, Rc<Vec<String>>>
 }
 
 impl Evaluator {
-  pub fn new() -> Self {
+  pub fn new(diagnostics: DiagnosticGlossaryCell) -> Self {
     Self { 
       last_value: None, 
-      variables: HashMap::new() 
+      scopes: vec![HashMap::new()],
+      diagnostics,
+      halted: false,
+      control_flow: None,
+      tail_call: None,
+      call_depth: 0,
+      max_call_depth: Self::DEFAULT_MAX_CALL_DEPTH,
+      depth: 0,
+      max_depth: Self::DEFAULT_MAX_DEPTH,
+      structs: HashMap::new()
     }
   }
-}
 
-impl SyntaxTreeVisitor for Evaluator {
-  fn visit_number(&mut self, number: &NumberExpression) {
-    self.last_value = Some(number.number);
+  pub const DEFAULT_MAX_CALL_DEPTH: usize = 10_000;
+
+  // A nested expression takes under a kilobyte of host stack in a debug
+  // build, calls included. The stack grows as evaluation needs it, so this
+  // only bounds the memory a runaway script takes.
+  pub const DEFAULT_MAX_DEPTH: usize = 100_000;
+
+  pub fn with_max_call_depth(mut self, max_call_depth: usize) -> Self {
+    self.max_call_depth = max_call_depth;
+    return self;
   }
 
-  fn visit_let_statement(&mut self, let_statement: &LetStatement) {
-    self.visit_expression(&let_statement.initialiser);
-    self.variables.insert(let_statement.identifier.span.literal.clone(), self.last_value.unwrap());
+  fn evaluate(&mut self, expression: &SyntaxTreeExpression) -> Option<Value> {
+    self.visit_expression(expression);
+    if self.halted || self.control_flow.is_some() {
+      return None;
+    }
+    return self.last_value.clone();
   }
-  
-  fn visit_variable_expression(&mut self, variable_expression: &VariableExpression) {
-    self.last_value = Some(*self.variables.get(&variable_expression.identifier.span.literal).unwrap());
+
+  fn begin_scope(&mut self) {
+    self.scopes.push(HashMap::new());
   }
 
-  fn visit_error(&mut self, span: &TextSpan) {
-    todo!()
+  fn end_scope(&mut self) {
+    self.scopes.pop();
   }
 
-  fn visit_binary_expression(&mut self, expr: &BinaryExpression) {
-    self.visit_expression(&expr.left);
-    let left = self.last_value.unwrap();
-    self.visit_expression(&expr.right);
-    let right = self.last_value.unwrap();
-    self.last_value = Some(match expr.operator.kind {
+  fn declare(&mut self, name: &str, value: Value) {
+    self.scopes.last_mut().unwrap().insert(name.to_string(), value);
+  }
+
+  fn lookup(&self, name: &str) -> Option<&Value> {
+    return self.scopes.iter().rev().find_map(|scope| scope.get(name));
+  }
+
+  // The binding the name refers to, in whichever scope it was declared
+  fn lookup_mut(&mut self, name: &str) -> Option<&mut Value> {
+    return self.scopes.iter_mut().rev().find_map(|scope| scope.get_mut(name));
+  }
+
+  // A call runs in a frame of its own on top of the globals, with the
+  // parameters and the function itself bound. A tail call made by the body
+  // takes over the frame once the body is left, so a chain of tail calls
+  // runs in a loop here instead of nesting.
+  fn call(&mut self, function: Rc<FunctionValue>, arguments: Vec<Value>, span: &TextSpan) {
+    if self.call_depth == self.max_call_depth {
+      self.diagnostics.borrow_mut().report_stack_overflow(span.clone(), self.max_call_depth);
+      return self.halt();
+    }
+    self.call_depth += 1;
+    let caller = self.scopes.split_off(1);
+    let mut next = Some((function, arguments));
+    while let Some((function, arguments)) = next.take() {
+      let mut frame: HashMap<String, Value> = function.parameters.iter().cloned().zip(arguments).collect();
+      if let Some(name) = &function.name {
+        frame.entry(name.clone()).or_insert_with(|| Value::Function(Rc::clone(&function)));
+      }
+      self.scopes.truncate(1);
+      self.scopes.push(function.captures.clone());
+      self.scopes.push(frame);
+      self.visit_tail_expression(&function.body);
+      if self.control_flow == Some(ControlFlow::Return) {
+        self.control_flow = None;
+      }
+      next = self.tail_call.take();
+    }
+    self.scopes.truncate(1);
+    self.scopes.extend(caller);
+    self.call_depth -= 1;
+  }
+
+  // Visits an expression whose value is the value of the call being run, a
+  // call in there is a tail call
+  fn visit_tail_expression(&mut self, expression: &SyntaxTreeExpression) {
+    if self.halted || self.control_flow.is_some() || !self.enter(expression) {
+      return;
+    }
+    ensure_stack(|| match &expression.kind {
+      SyntaxTreeExpressionKind::Block(block_expression) => self.evaluate_block(block_expression, true),
+      SyntaxTreeExpressionKind::If(if_expression) => self.evaluate_if(if_expression, true),
+      SyntaxTreeExpressionKind::Parenthesised(parenthesised) => self.visit_tail_expression(&parenthesised.expression),
+      SyntaxTreeExpressionKind::Call(call_expression) => self.evaluate_call(call_expression, true),
+      _ => self.do_visit_expression(expression)
+    });
+    self.depth -= 1;
+  }
+
+  // Counts an expression being visited. Once expressions nest deeper than
+  // allowed, a stack overflow is reported instead.
+  fn enter(&mut self, expression: &SyntaxTreeExpression) -> bool {
+    if self.depth == self.max_depth {
+      self.diagnostics.borrow_mut().report_nesting_overflow(expression.span.clone(), self.max_depth);
+      self.halt();
+      return false;
+    }
+    self.depth += 1;
+    return true;
+  }
+
+  fn evaluate_call(&mut self, call_expression: &CallExpression, tail: bool) {
+    let Some(callee) = self.evaluate(&call_expression.callee) else { return };
+    let mut arguments = Vec::new();
+    for argument in &call_expression.arguments {
+      let Some(argument) = self.evaluate(argument) else { return };
+      arguments.push(argument);
+    }
+    let function = match callee {
+      Value::Function(function) => function,
+      Value::Builtin(builtin) => return self.call_builtin(builtin, arguments, call_expression),
+      _ => return self.runtime_error(format!("Cannot call a value of type {}", callee.type_name()), &call_expression.callee.span)
+    };
+    if !self.check_argument_count(function.name.as_deref(), function.parameters.len(), arguments.len(), &call_expression.callee.span) {
+      return;
+    }
+    if tail {
+      self.tail_call = Some((function, arguments));
+      return;
+    }
+    self.call(function, arguments, &call_expression.callee.span);
+  }
+
+  fn check_argument_count(&mut self, name: Option<&str>, expected: usize, given: usize, span: &TextSpan) -> bool {
+    if expected != given {
+      self.diagnostics.borrow_mut().report_wrong_argument_count(span.clone(), name, expected, given, None);
+      self.halt();
+    }
+    return expected == given;
+  }
+
+  fn call_builtin(&mut self, builtin: Builtin, arguments: Vec<Value>, call_expression: &CallExpression) {
+    if !self.check_argument_count(Some(builtin.name()), builtin.arity(), arguments.len(), &call_expression.callee.span) {
+      return;
+    }
+    let expected = |expected: &str, argument: &Value| Err((
+      format!("{}() expects {}, found {}", builtin.name(), expected, argument.type_name()), &call_expression.arguments[0].span
+    ));
+    let result = match (builtin, &arguments[0]) {
+      (Builtin::Len, Value::Array(elements)) => Ok(Value::Integer(elements.len() as i64)),
+      (Builtin::Len, Value::Map(map)) => Ok(Value::Integer(map.len() as i64)),
+      (Builtin::Len, Value::String(string)) => Ok(Value::Integer(string.chars().count() as i64)),
+      (Builtin::Len, argument) => expected("an array, a map or a string", argument),
+      (Builtin::Keys, Value::Map(map)) => Ok(Value::Array(Rc::new(map.entries().map(|(key, _)| Value::from(key.clone())).collect()))),
+      (Builtin::Values, Value::Map(map)) => Ok(Value::Array(Rc::new(map.entries().map(|(_, value)| value.clone()).collect()))),
+      (Builtin::Keys | Builtin::Values, argument) => expected("a map", argument),
+      (Builtin::Contains, Value::Map(map)) => Ok(Value::Boolean(MapKey::from_value(&arguments[1]).is_some_and(|key| map.contains_key(&key)))),
+      (Builtin::Contains, Value::Array(elements)) => {
+        Ok(Value::Boolean(elements.iter().any(|element| Self::values_equal(element, &arguments[1]))))
+      }
+      (Builtin::Contains, Value::String(string)) => match &arguments[1] {
+        Value::String(part) => Ok(Value::Boolean(string.contains(part.as_str()))),
+        part => Err((format!("contains() expects a string to look for in a string, found {}", part.type_name()), &call_expression.arguments[1].span))
+      },
+      (Builtin::Contains, argument) => expected("a map, an array or a string", argument)
+    };
+    match result {
+      Ok(value) => self.last_value = Some(value),
+      Err((message, span)) => self.runtime_error(message, span)
+    }
+  }
+
+  // Negative indexes count from the end, -1 is the last element
+  fn element_index(index: i64, length: usize) -> Result<usize, String> {
+    let position = if index < 0 { index + length as i64 } else { index };
+    if position < 0 || position >= length as i64 {
+      return Err(format!("Index {} out of bounds for length {}", index, length));
+    }
+    return Ok(position as usize);
+  }
+
+  // Bounds of a slice count from the end when negative like indexes, `xs[1..-1]`
+  // drops the first and the last element
+  fn slice_bounds(range: &RangeValue, length: usize) -> Result<(usize, usize), String> {
+    if range.step != 1 {
+      return Err(format!("Cannot slice with a step, found step {}", range.step));
+    }
+    let bound = |bound: i64| if bound < 0 { bound + length as i64 } else { bound };
+    let start = bound(range.start);
+    let end = bound(range.end).saturating_add(range.inclusive as i64);
+    if start < 0 || end > length as i64 || start > end {
+      return Err(format!("Slice {} out of bounds for length {}", range, length));
+    }
+    return Ok((start as usize, end as usize));
+  }
+
+  fn index_array(elements: &[Value], index: &Value) -> Result<Value, String> {
+    return match index {
+      Value::Integer(index) => Ok(elements[Self::element_index(*index, elements.len())?].clone()),
+      Value::Range(range) => {
+        let (start, end) = Self::slice_bounds(range, elements.len())?;
+        Ok(Value::Array(Rc::new(elements[start..end].to_vec())))
+      }
+      _ => Err(format!("Expected an integer or a range to index with, found {}", index.type_name()))
+    };
+  }
+
+  fn map_key(key: &Value) -> Result<MapKey, String> {
+    return MapKey::from_value(key).ok_or_else(|| format!("Cannot use a value of type {} as a map key", key.type_name()));
+  }
+
+  fn missing_key(key: &MapKey) -> String {
+    return format!("Key {} not found in map", match key {
+      MapKey::String(string) => format!("{:?}", string),
+      MapKey::Integer(integer) => integer.to_string(),
+      MapKey::Boolean(boolean) => boolean.to_string()
+    });
+  }
+
+  fn index_map(map: &MapValue, key: &Value) -> Result<Value, String> {
+    let key = Self::map_key(key)?;
+    return map.get(&key).cloned().ok_or_else(|| Self::missing_key(&key));
+  }
+
+  fn missing_field(instance: &StructValue, field: &Token) -> String {
+    return format!("Struct '{}' has no field '{}'", instance.name, field.span.literal);
+  }
+
+  fn not_a_struct(target: &Value, field: &Token) -> String {
+    return format!("Cannot access field '{}' on a value of type {}", field.span.literal, target.type_name());
+  }
+
+  fn field(target: &Value, field: &Token) -> Result<Value, String> {
+    return match target {
+      Value::Struct(instance) => instance.get(&field.span.literal).cloned().ok_or_else(|| Self::missing_field(instance, field)),
+      _ => Err(Self::not_a_struct(target, field))
+    };
+  }
+
+  // Evaluates the indexes of an assignment target, before the assigned value
+  fn evaluate_place(&mut self, target: &SyntaxTreeExpression) -> Option<Place> {
+    return match &target.kind {
+      SyntaxTreeExpressionKind::Variable(variable) => Some(Place { identifier: variable.identifier.clone(), accessors: Vec::new() }),
+      SyntaxTreeExpressionKind::Index(index_expression) => {
+        let mut place = self.evaluate_place(&index_expression.target)?;
+        let index = self.evaluate(&index_expression.index)?;
+        place.accessors.push(Accessor::Index(index, index_expression.index.span.clone()));
+        Some(place)
+      }
+      SyntaxTreeExpressionKind::Field(field_expression) => {
+        let mut place = self.evaluate_place(&field_expression.target)?;
+        place.accessors.push(Accessor::Field(field_expression.field.clone()));
+        Some(place)
+      }
+      _ => {
+        self.halt();
+        None
+      }
+    };
+  }
+
+  // The arrays, maps and structs on the way to the part are copied if they
+  // are shared, so the part can be changed without changing any other holder
+  // of them. With `insert`, a missing key of the last map is added.
+  fn place_mut(&mut self, place: &Place, insert: bool) -> Result<&mut Value, (String, TextSpan)> {
+    let identifier = &place.identifier;
+    let Some(mut value) = self.lookup_mut(&identifier.span.literal) else {
+      return Err((format!("Undeclared variable '{}'", identifier.span.literal), identifier.span.clone()));
+    };
+    for (position, accessor) in place.accessors.iter().enumerate() {
+      value = match (value, accessor) {
+        (Value::Struct(instance), Accessor::Field(field)) => {
+          let missing = Self::missing_field(instance, field);
+          Rc::make_mut(instance).get_mut(&field.span.literal).ok_or((missing, field.span.clone()))?
+        }
+        (value, Accessor::Field(field)) => return Err((Self::not_a_struct(value, field), field.span.clone())),
+        (Value::Array(elements), Accessor::Index(Value::Integer(index), span)) => {
+          let elements = Rc::make_mut(elements);
+          let position = Self::element_index(*index, elements.len()).map_err(|message| (message, span.clone()))?;
+          &mut elements[position]
+        }
+        (Value::Array(_), Accessor::Index(Value::Range(_), span)) => return Err(("Cannot assign to a slice, only to an element".to_string(), span.clone())),
+        (Value::Array(_), Accessor::Index(index, span)) => {
+          return Err((format!("Expected an integer to index with, found {}", index.type_name()), span.clone()));
+        }
+        (Value::Map(map), Accessor::Index(key, span)) => {
+          let key = Self::map_key(key).map_err(|message| (message, span.clone()))?;
+          let map = Rc::make_mut(map);
+          if insert && position + 1 == place.accessors.len() {
+            map.get_or_insert(key)
+          }
+          else {
+            let missing = Self::missing_key(&key);
+            map.get_mut(&key).ok_or((missing, span.clone()))?
+          }
+        }
+        (value, Accessor::Index(_, span)) => return Err((format!("Cannot index a value of type {}", value.type_name()), span.clone()))
+      };
+    }
+    return Ok(value);
+  }
+
+  fn declare_function(&mut self, function_declaration: &FunctionDeclaration) {
+    let name = function_declaration.identifier.span.literal.clone();
+    let function = FunctionValue {
+      name: Some(name.clone()),
+      parameters: function_declaration.parameters.iter().map(|parameter| parameter.span.literal.clone()).collect(),
+      body: Rc::clone(&function_declaration.body),
+      captures: HashMap::new()
+    };
+    self.declare(&name, Value::Function(Rc::new(function)));
+  }
+
+  // Functions are bound from the start of the program, like the SymbolChecker
+  // binds them
+  pub fn declare_functions(&mut self, statements: &[SyntaxTreeStatement]) {
+    for statement in statements {
+      if let SyntaxTreeStatementKind::Function(function_declaration) = &statement.kind {
+        self.declare_function(function_declaration);
+      }
+    }
+  }
+
+  // The last statement of a block in tail position is in tail position too
+  fn evaluate_block(&mut self, block_expression: &BlockExpression, tail: bool) {
+    self.begin_scope();
+    for (index, statement) in block_expression.statements.iter().enumerate() {
+      match &statement.kind {
+        SyntaxTreeStatementKind::Expression(expression) if tail && index + 1 == block_expression.statements.len() => {
+          self.visit_tail_expression(expression);
+        }
+        _ => self.visit_statement(statement)
+      }
+    }
+    self.end_scope();
+    if !self.halted && self.control_flow.is_none() && !block_expression.is_expression() {
+      self.last_value = Some(Value::Unit);
+    }
+  }
+
+  fn evaluate_if(&mut self, if_expression: &IfExpression, tail: bool) {
+    let Some(condition) = self.evaluate_condition(&if_expression.condition) else { return };
+    let branch = if condition { Some(&if_expression.then_branch) } else { if_expression.else_branch.as_ref() };
+    match branch {
+      Some(branch) if tail => self.visit_tail_expression(branch),
+      Some(branch) => self.visit_expression(branch),
+      None => self.last_value = Some(Value::Unit)
+    }
+  }
+
+  // Takes the jump out of the loop body, if it is the loop's, and tells
+  // whether the loop is done
+  fn end_iteration(&mut self) -> bool {
+    return match self.control_flow {
+      Some(ControlFlow::Break) => {
+        self.control_flow = None;
+        true
+      }
+      Some(ControlFlow::Continue) => {
+        self.control_flow = None;
+        false
+      }
+      Some(ControlFlow::Return) => true,
+      None => self.halted
+    };
+  }
+
+  fn runtime_error(&mut self, message: String, span: &TextSpan) {
+    self.diagnostics.borrow_mut().report_error(message, span.clone());
+    self.halt();
+  }
+
+  fn halt(&mut self) {
+    self.last_value = None;
+    self.halted = true;
+  }
+
+  // Evaluates a condition, anything but a boolean is an error
+  fn evaluate_condition(&mut self, expression: &SyntaxTreeExpression) -> Option<bool> {
+    return match self.evaluate(expression)? {
+      Value::Boolean(value) => Some(value),
+      value => {
+        self.diagnostics.borrow_mut().report_expected_boolean(expression.span.clone(), value.type_name());
+        self.halt();
+        None
+      }
+    };
+  }
+
+  // `&&` and `||` only evaluate their right operand if the left one doesn't decide the result
+  fn evaluate_logical_operation(&mut self, expr: &BinaryExpression) {
+    let Some(left) = self.evaluate_condition(&expr.left) else { return };
+    if left == (expr.operator.kind == BinaryOperatorKind::LogicalOr) {
+      self.last_value = Some(Value::Boolean(left));
+      return;
+    }
+    let Some(right) = self.evaluate_condition(&expr.right) else { return };
+    self.last_value = Some(Value::Boolean(right));
+  }
+
+  // Numbers compare across types with the same promotion as arithmetic, booleans
+  // only compare for equality. Arrays, maps and structs are equal when their
+  // elements are, compared the same way.
+  fn evaluate_comparison(kind: &BinaryOperatorKind, left: &Value, right: &Value) -> Option<Value> {
+    let ordering = match (left, right) {
+      (Value::Integer(left), Value::Integer(right)) => left.partial_cmp(right),
+      (Value::String(left), Value::String(right)) => left.partial_cmp(right),
+      (Value::Boolean(left), Value::Boolean(right)) if matches!(kind, BinaryOperatorKind::Equal | BinaryOperatorKind::NotEqual) => left.partial_cmp(right),
+      (Value::Array(left), Value::Array(right)) if matches!(kind, BinaryOperatorKind::Equal | BinaryOperatorKind::NotEqual) => {
+        let equal = left.len() == right.len() && left.iter().zip(right.iter()).all(|(left, right)| Self::values_equal(left, right));
+        equal.then_some(Ordering::Equal)
+      }
+      (Value::Map(left), Value::Map(right)) if matches!(kind, BinaryOperatorKind::Equal | BinaryOperatorKind::NotEqual) => {
+        let equal = left.len() == right.len() && left.entries().all(|(key, left)| {
+          return right.get(key).is_some_and(|right| Self::values_equal(left, right));
+        });
+        equal.then_some(Ordering::Equal)
+      }
+      (Value::Struct(left), Value::Struct(right)) if matches!(kind, BinaryOperatorKind::Equal | BinaryOperatorKind::NotEqual) => {
+        let equal = left.name == right.name && left.fields.len() == right.fields.len() &&
+          left.fields.iter().zip(right.fields.iter()).all(|((left_name, left), (right_name, right))| {
+            return left_name == right_name && Self::values_equal(left, right);
+          });
+        equal.then_some(Ordering::Equal)
+      }
+      _ => match (left.as_big_integer(), right.as_big_integer()) {
+        (Some(left), Some(right)) => left.partial_cmp(&right),
+        _ => left.as_float()?.partial_cmp(&right.as_float()?)
+      }
+    };
+    // NaN is unordered, so every comparison with it but `!=` is false
+    let result = match kind {
+      BinaryOperatorKind::Equal => ordering == Some(Ordering::Equal),
+      BinaryOperatorKind::NotEqual => ordering != Some(Ordering::Equal),
+      BinaryOperatorKind::LessThan => ordering == Some(Ordering::Less),
+      BinaryOperatorKind::LessThanOrEqual => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+      BinaryOperatorKind::GreaterThan => ordering == Some(Ordering::Greater),
+      BinaryOperatorKind::GreaterThanOrEqual => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+      _ => return None
+    };
+    return Some(Value::Boolean(result));
+  }
+
+  // Whether `==` holds, for values it compares, or the values are the same
+  fn values_equal(left: &Value, right: &Value) -> bool {
+    return match Self::evaluate_comparison(&BinaryOperatorKind::Equal, left, right) {
+      Some(result) => result == Value::Boolean(true),
+      None => left == right
+    };
+  }
+
+  // The operator token is only used to name the operator in error messages
+  fn evaluate_binary_operation(kind: &BinaryOperatorKind, operator: &Token, left: &Value, right: &Value) -> Result<Value, String> {
+    let unsupported = || format!(
+      "Unsupported operand types for '{}': {} and {}", operator.span.literal, left.type_name(), right.type_name()
+    );
+    return match (left, right) {
+      _ if kind.is_range() => Self::evaluate_range_operation(kind, left, right).unwrap_or_else(|| Err(unsupported())),
+      _ if kind.is_comparison() => Self::evaluate_comparison(kind, left, right).ok_or_else(unsupported),
+      (Value::Integer(left), Value::Integer(right)) => Self::evaluate_integer_operation(kind, *left, *right).unwrap_or_else(|| Err(unsupported())),
+      (Value::String(left), Value::String(right)) => Self::evaluate_string_operation(kind, left, right).ok_or_else(unsupported),
+      (Value::Array(left), Value::Array(right)) if *kind == BinaryOperatorKind::Plus => {
+        Ok(Value::Array(Rc::new(left.iter().chain(right.iter()).cloned().collect())))
+      }
+      _ => match (left.as_big_integer(), right.as_big_integer()) {
+        (Some(left), Some(right)) => Self::evaluate_big_integer_operation(kind, left, right).unwrap_or_else(|| Err(unsupported())),
+        _ => match (left.as_float(), right.as_float()) {
+          (Some(left), Some(right)) => Self::evaluate_float_operation(kind, left, right).ok_or_else(unsupported),
+          _ => Err(unsupported())
+        }
+      }
+    };
+  }
+
+  fn evaluate_range_operation(kind: &BinaryOperatorKind, left: &Value, right: &Value) -> Option<Result<Value, String>> {
+    return match (kind, left, right) {
+      (BinaryOperatorKind::Range, Value::Integer(start), Value::Integer(end)) => Some(Ok(Value::Range(RangeValue::new(*start, *end, false)))),
+      (BinaryOperatorKind::RangeInclusive, Value::Integer(start), Value::Integer(end)) => Some(Ok(Value::Range(RangeValue::new(*start, *end, true)))),
+      (BinaryOperatorKind::Step, Value::Range(_), Value::Integer(0)) => Some(Err("Range step cannot be zero".to_string())),
+      (BinaryOperatorKind::Step, Value::Range(range), Value::Integer(step)) => Some(Ok(Value::Range(RangeValue { step: *step, ..range.clone() }))),
+      _ => None
+    };
+  }
+
+  fn evaluate_string_operation(kind: &BinaryOperatorKind, left: &str, right: &str) -> Option<Value> {
+    return match kind {
+      BinaryOperatorKind::Plus => Some(Value::String(format!("{}{}", left, right))),
+      _ => None
+    };
+  }
+
+  // None for operators integers don't support
+  fn evaluate_integer_operation(kind: &BinaryOperatorKind, left: i64, right: i64) -> Option<Result<Value, String>> {
+    let result = match kind {
+      BinaryOperatorKind::Plus => left.checked_add(right),
+      BinaryOperatorKind::Minus => left.checked_sub(right),
+      BinaryOperatorKind::Multiply => left.checked_mul(right),
+      BinaryOperatorKind::Divide | BinaryOperatorKind::Modulo if right == 0 => {
+        return Some(Err("Division by zero".to_string()));
+      }
+      BinaryOperatorKind::Divide => left.checked_div(right),
+      BinaryOperatorKind::Modulo => left.checked_rem(right),
+      BinaryOperatorKind::BitwiseAnd => Some(left & right),
+      BinaryOperatorKind::BitwiseOr => Some(left | right),
+      BinaryOperatorKind::BitwiseXor => Some(left ^ right),
+      BinaryOperatorKind::ShiftLeft | BinaryOperatorKind::ShiftRight => {
+        let Some(amount) = u32::try_from(right).ok().filter(|amount| *amount < i64::BITS) else {
+          return Some(Err(format!("Shift amount {} out of range", right)));
+        };
+        if *kind == BinaryOperatorKind::ShiftRight {
+          Some(left >> amount)
+        }
+        else {
+          // Bits shifted out of the value are an overflow like for multiplication
+          Some(left << amount).filter(|shifted| shifted >> amount == left)
+        }
+      }
+      BinaryOperatorKind::Power => {
+        // A negative exponent can't produce an integer, fall back to float power
+        if right < 0 {
+          return Some(Ok(Value::Float((left as f64).powf(right as f64))));
+        }
+        u32::try_from(right).ok().and_then(|exponent| left.checked_pow(exponent))
+      }
+      _ => return None
+    };
+    return Some(result.map(Value::Integer).ok_or("Integer overflow".to_string()));
+  }
+
+  fn evaluate_big_integer_operation(kind: &BinaryOperatorKind, left: BigInt, right: BigInt) -> Option<Result<Value, String>> {
+    let result = match kind {
       BinaryOperatorKind::Plus => left + right,
       BinaryOperatorKind::Minus => left - right,
       BinaryOperatorKind::Multiply => left * right,
+      BinaryOperatorKind::Divide | BinaryOperatorKind::Modulo if right.is_zero() => {
+        return Some(Err("Division by zero".to_string()));
+      }
       BinaryOperatorKind::Divide => left / right,
+      BinaryOperatorKind::Modulo => left % right,
       BinaryOperatorKind::BitwiseAnd => left & right,
       BinaryOperatorKind::BitwiseOr => left | right,
       BinaryOperatorKind::BitwiseXor => left ^ right,
-      BinaryOperatorKind::Power => left.pow(right as u32)
+      BinaryOperatorKind::ShiftLeft | BinaryOperatorKind::ShiftRight => {
+        let Some(amount) = right.to_usize() else {
+          return Some(Err(format!("Shift amount {} out of range", right)));
+        };
+        if *kind == BinaryOperatorKind::ShiftRight { left >> amount } else { left << amount }
+      }
+      BinaryOperatorKind::Power => {
+        if right < BigInt::zero() {
+          let base = left.to_f64().unwrap_or(f64::NAN);
+          return Some(Ok(Value::Float(base.powf(right.to_f64().unwrap_or(f64::NAN)))));
+        }
+        let Some(exponent) = right.to_u32() else {
+          return Some(Err("Exponent too large".to_string()));
+        };
+        left.pow(exponent)
+      }
+      _ => return None
+    };
+    return Some(Ok(Value::BigInteger(result)));
+  }
+
+  fn evaluate_float_operation(kind: &BinaryOperatorKind, left: f64, right: f64) -> Option<Value> {
+    return match kind {
+      BinaryOperatorKind::Plus => Some(Value::Float(left + right)),
+      BinaryOperatorKind::Minus => Some(Value::Float(left - right)),
+      BinaryOperatorKind::Multiply => Some(Value::Float(left * right)),
+      BinaryOperatorKind::Divide => Some(Value::Float(left / right)),
+      BinaryOperatorKind::Modulo => Some(Value::Float(left % right)),
+      BinaryOperatorKind::Power => Some(Value::Float(left.powf(right))),
+      _ => None
+    };
+  }
+}
+
+impl SyntaxTreeVisitor for Evaluator {
+  fn visit_number(&mut self, number: &NumberExpression) {
+    self.last_value = Some(Value::from(number.number.clone()));
+  }
+
+  fn visit_let_statement(&mut self, let_statement: &LetStatement) {
+    if let Some(value) = self.evaluate(&let_statement.initialiser) {
+      self.declare(&let_statement.identifier.span.literal, value);
+    }
+  }
+  
+  fn visit_variable_expression(&mut self, variable_expression: &VariableExpression) {
+    let identifier = &variable_expression.identifier;
+    let value = match self.lookup(&identifier.span.literal) {
+      Some(value) => value.clone(),
+      None => match Builtin::lookup(&identifier.span.literal) {
+        Some(builtin) => Value::Builtin(builtin),
+        None => {
+          self.diagnostics.borrow_mut().report_undeclared_variable(identifier);
+          return self.halt();
+        }
+      }
+    };
+    self.last_value = Some(value);
+  }
+
+  fn visit_boolean(&mut self, boolean: &BooleanExpression) {
+    self.last_value = Some(Value::Boolean(boolean.value));
+  }
+
+  fn visit_string(&mut self, string: &StringExpression) {
+    self.last_value = Some(Value::String(string.value.clone()));
+  }
+
+  fn visit_interpolation_expression(&mut self, interpolation_expression: &InterpolationExpression) {
+    let mut result = String::new();
+    for part in &interpolation_expression.parts {
+      match part {
+        InterpolationPart::Text(text) => result.push_str(text),
+        InterpolationPart::Expression(expr) => {
+          let Some(value) = self.evaluate(expr) else { return };
+          result.push_str(&value.to_string());
+        }
+      }
+    }
+    self.last_value = Some(Value::String(result));
+  }
+
+  // The value of an assignment is the value assigned
+  fn visit_assignment_expression(&mut self, assignment_expression: &AssignmentExpression) {
+    let Some(place) = self.evaluate_place(&assignment_expression.target) else { return };
+    let Some(mut value) = self.evaluate(&assignment_expression.value) else { return };
+    let operator = &assignment_expression.operator;
+    let result = self.place_mut(&place, operator.kind.is_none()).and_then(|current| {
+      if let Some(kind) = &operator.kind {
+        value = Self::evaluate_binary_operation(kind, &operator.token, current, &value).map_err(|message| (message, operator.token.span.clone()))?;
+      }
+      *current = value.clone();
+      return Ok(());
     });
+    match result {
+      Ok(()) => self.last_value = Some(value),
+      Err((message, span)) => self.runtime_error(message, &span)
+    }
+  }
+
+  fn visit_call_expression(&mut self, call_expression: &CallExpression) {
+    self.evaluate_call(call_expression, false);
+  }
+
+  fn visit_array_expression(&mut self, array_expression: &ArrayExpression) {
+    let mut elements = Vec::new();
+    for element in &array_expression.elements {
+      let Some(element) = self.evaluate(element) else { return };
+      elements.push(element);
+    }
+    self.last_value = Some(Value::Array(Rc::new(elements)));
+  }
+
+  fn visit_index_expression(&mut self, index_expression: &IndexExpression) {
+    let Some(target) = self.evaluate(&index_expression.target) else { return };
+    let Some(index) = self.evaluate(&index_expression.index) else { return };
+    let result = match &target {
+      Value::Array(elements) => Self::index_array(elements, &index),
+      Value::Map(map) => Self::index_map(map, &index),
+      _ => return self.runtime_error(format!("Cannot index a value of type {}", target.type_name()), &index_expression.target.span)
+    };
+    match result {
+      Ok(value) => self.last_value = Some(value),
+      Err(message) => self.runtime_error(message, &index_expression.index.span)
+    }
+  }
+
+  fn visit_map_expression(&mut self, map_expression: &MapExpression) {
+    let mut map = MapValue::default();
+    for entry in &map_expression.entries {
+      let Some(key) = self.evaluate(&entry.key) else { return };
+      let key = match Self::map_key(&key) {
+        Ok(key) => key,
+        Err(message) => return self.runtime_error(message, &entry.key.span)
+      };
+      let Some(value) = self.evaluate(&entry.value) else { return };
+      map.insert(key, value);
+    }
+    self.last_value = Some(Value::Map(Rc::new(map)));
+  }
+
+  fn visit_struct_declaration(&mut self, struct_declaration: &StructDeclaration) {
+    let fields = struct_declaration.fields.iter().map(|field| field.span.literal.clone()).collect();
+    self.structs.insert(struct_declaration.identifier.span.literal.clone(), Rc::new(fields));
+    self.last_value = Some(Value::Unit);
+  }
+
+  // The initialisers run in the order they are written in, the fields are
+  // kept in the order they are declared in
+  fn visit_struct_literal_expression(&mut self, struct_literal_expression: &StructLiteralExpression) {
+    let identifier = &struct_literal_expression.identifier;
+    let Some(fields) = self.structs.get(&identifier.span.literal).cloned() else {
+      self.diagnostics.borrow_mut().report_undeclared_struct(identifier);
+      return self.halt();
+    };
+    let mut values = HashMap::new();
+    for initialiser in &struct_literal_expression.initialisers {
+      let field = &initialiser.field;
+      if !fields.contains(&field.span.literal) {
+        return self.runtime_error(format!("Struct '{}' has no field '{}'", identifier.span.literal, field.span.literal), &field.span);
+      }
+      let Some(value) = self.evaluate(&initialiser.value) else { return };
+      values.insert(field.span.literal.clone(), value);
+    }
+    let mut instance = StructValue { name: identifier.span.literal.clone(), fields: Vec::new() };
+    for field in fields.iter() {
+      let Some(value) = values.remove(field) else {
+        return self.runtime_error(format!("Missing field '{}' in initialiser of struct '{}'", field, identifier.span.literal), &identifier.span);
+      };
+      instance.fields.push((field.clone(), value));
+    }
+    self.last_value = Some(Value::Struct(Rc::new(instance)));
+  }
+
+  fn visit_field_expression(&mut self, field_expression: &FieldExpression) {
+    let Some(target) = self.evaluate(&field_expression.target) else { return };
+    match Self::field(&target, &field_expression.field) {
+      Ok(value) => self.last_value = Some(value),
+      Err(message) => self.runtime_error(message, &field_expression.field.span)
+    }
+  }
+
+  fn visit_block_expression(&mut self, block_expression: &BlockExpression) {
+    self.evaluate_block(block_expression, false);
+  }
+
+  fn visit_if_expression(&mut self, if_expression: &IfExpression) {
+    self.evaluate_if(if_expression, false);
+  }
+
+  // Trees with syntax errors aren't run, there's no value to give them
+  fn visit_error(&mut self, _span: &TextSpan) {
+    self.halt();
+  }
+
+  fn visit_statement(&mut self, statement: &SyntaxTreeStatement) {
+    if self.halted || self.control_flow.is_some() {
+      return;
+    }
+    self.do_visit_statement(statement);
+  }
+
+  fn visit_expression(&mut self, expression: &SyntaxTreeExpression) {
+    if self.halted || self.control_flow.is_some() || !self.enter(expression) {
+      return;
+    }
+    ensure_stack(|| self.do_visit_expression(expression));
+    self.depth -= 1;
+  }
+
+  fn visit_while_statement(&mut self, while_statement: &WhileStatement) {
+    while let Some(true) = self.evaluate_condition(&while_statement.condition) {
+      self.visit_expression(&while_statement.body);
+      if self.end_iteration() {
+        break;
+      }
+    }
+    if !self.halted && self.control_flow.is_none() {
+      self.last_value = Some(Value::Unit);
+    }
+  }
+
+  // Each value is bound in a scope of its own that ends with the iteration
+  fn visit_for_statement(&mut self, for_statement: &ForStatement) {
+    let Some(iterable) = self.evaluate(&for_statement.iterable) else { return };
+    let values: Box<dyn Iterator<Item = Value>> = match iterable {
+      Value::Range(range) => Box::new(range.values().map(Value::Integer)),
+      Value::Array(elements) => Box::new((0..elements.len()).map(move |index| elements[index].clone())),
+      // A map is iterated over its keys, in insertion order
+      Value::Map(map) => Box::new(map.entries().map(|(key, _)| Value::from(key.clone())).collect::<Vec<_>>().into_iter()),
+      _ => {
+        self.diagnostics.borrow_mut().report_not_iterable(for_statement.iterable.span.clone(), iterable.type_name());
+        return self.halt();
+      }
+    };
+    for value in values {
+      self.begin_scope();
+      self.declare(&for_statement.identifier.span.literal, value);
+      self.visit_expression(&for_statement.body);
+      self.end_scope();
+      if self.end_iteration() {
+        break;
+      }
+    }
+    if !self.halted && self.control_flow.is_none() {
+      self.last_value = Some(Value::Unit);
+    }
+  }
+
+  fn visit_function_declaration(&mut self, function_declaration: &FunctionDeclaration) {
+    self.declare_function(function_declaration);
+    self.last_value = Some(Value::Unit);
+  }
+
+  // Every variable in scope, global or local, is copied into the closure.
+  // Only globals declared after it are looked up when it is called.
+  fn visit_closure_expression(&mut self, closure_expression: &ClosureExpression) {
+    let captures = self.scopes.iter().flatten().map(|(name, value)| (name.clone(), value.clone())).collect();
+    let function = FunctionValue {
+      name: None,
+      parameters: closure_expression.parameters.iter().map(|parameter| parameter.span.literal.clone()).collect(),
+      body: Rc::clone(&closure_expression.body),
+      captures
+    };
+    self.last_value = Some(Value::Function(Rc::new(function)));
+  }
+
+  // The returned value is in tail position, wherever the return is
+  fn visit_return_statement(&mut self, return_statement: &ReturnStatement) {
+    match &return_statement.value {
+      Some(value) => self.visit_tail_expression(value),
+      None => self.last_value = Some(Value::Unit)
+    }
+    if !self.halted && self.control_flow.is_none() {
+      self.control_flow = Some(ControlFlow::Return);
+    }
+  }
+
+  fn visit_break_statement(&mut self, _keyword: &Token) {
+    self.control_flow = Some(ControlFlow::Break);
+  }
+
+  fn visit_continue_statement(&mut self, _keyword: &Token) {
+    self.control_flow = Some(ControlFlow::Continue);
+  }
+
+  fn visit_binary_expression(&mut self, expr: &BinaryExpression) {
+    if matches!(expr.operator.kind, BinaryOperatorKind::LogicalAnd | BinaryOperatorKind::LogicalOr) {
+      return self.evaluate_logical_operation(expr);
+    }
+    let Some(left) = self.evaluate(&expr.left) else { return };
+    let Some(right) = self.evaluate(&expr.right) else { return };
+    match Self::evaluate_binary_operation(&expr.operator.kind, &expr.operator.token, &left, &right) {
+      Ok(value) => self.last_value = Some(value),
+      Err(message) => self.runtime_error(message, &expr.operator.token.span)
+    }
   }
 
   fn visit_unary_expression(&mut self, unary_expression: &UnaryExpression) {
-    self.visit_expression(&unary_expression.operand);
-    let operand = self.last_value.unwrap();
-    self.last_value = Some(match unary_expression.operator.kind {
-      UnaryOperatorKind::Minus => -operand,
-      UnaryOperatorKind::BitwiseNot => !operand
-    })
-  }
-}
\ No newline at end of file
+    if unary_expression.operator.kind == UnaryOperatorKind::LogicalNot {
+      if let Some(operand) = self.evaluate_condition(&unary_expression.operand) {
+        self.last_value = Some(Value::Boolean(!operand));
+      }
+      return;
+    }
+    let Some(operand) = self.evaluate(&unary_expression.operand) else { return };
+    let result = match (&unary_expression.operator.kind, operand) {
+      (UnaryOperatorKind::Minus, Value::Integer(operand)) => operand.checked_neg().map(Value::Integer).ok_or("Integer overflow".to_string()),
+      (UnaryOperatorKind::Minus, Value::Float(operand)) => Ok(Value::Float(-operand)),
+      (UnaryOperatorKind::Minus, Value::BigInteger(operand)) => Ok(Value::BigInteger(-operand)),
+      (UnaryOperatorKind::BitwiseNot, Value::Integer(operand)) => Ok(Value::Integer(!operand)),
+      (UnaryOperatorKind::BitwiseNot, Value::BigInteger(operand)) => Ok(Value::BigInteger(!operand)),
+      (_, operand) => Err(format!(
+        "Unsupported operand type for '{}': {}", unary_expression.operator.token.span.literal, operand.type_name()
+      ))
+    };
+    match result {
+      Ok(value) => self.last_value = Some(value),
+      Err(message) => self.runtime_error(message, &unary_expression.operator.token.span)
+    }
+  }
+}
@@ -1,15 +1,46 @@
+use std::rc::Rc;
+
 use super::{
+  statement::SyntaxTreeStatement,
   lexer::{
     TextSpan,
     Token
   }, 
   types::{
+    _array::ArrayExpression,
+    _assignment::{
+      AssignmentExpression,
+      AssignmentOperator
+    },
     _binary::{
       BinaryExpression,
       BinaryOperator
     }, 
-    _number::NumberExpression, 
+    _block::BlockExpression,
+    _boolean::BooleanExpression,
+    _call::CallExpression,
+    _closure::ClosureExpression,
+    _if::IfExpression,
+    _index::IndexExpression,
+    _map::{
+      MapEntry,
+      MapExpression
+    },
+    _number::{
+      Number,
+      NumberExpression
+    }, 
     _parenthesis::ParenthesisExpression,
+    _struct::{
+      FieldExpression,
+      FieldInitialiser,
+      StructLiteralExpression
+    },
+    _string::{
+      InterpolationExpression,
+      InterpolationPart,
+      StringExpression
+    },
     _unary::{
       UnaryExpression,
       UnaryOperator
@@ -18,45 +49,128 @@ use super::{
   }
 };
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum SyntaxTreeExpressionKind {
   Number(NumberExpression),
+  Boolean(BooleanExpression),
   Binary(BinaryExpression),
   Unary(UnaryExpression),
   Parenthesised(ParenthesisExpression),
   Variable(VariableExpression),
-  Error(TextSpan)
+  String(StringExpression),
+  Interpolation(InterpolationExpression),
+  Block(BlockExpression),
+  If(IfExpression),
+  Assignment(AssignmentExpression),
+  Call(CallExpression),
+  Closure(ClosureExpression),
+  Array(ArrayExpression),
+  Index(IndexExpression),
+  Map(MapExpression),
+  StructLiteral(StructLiteralExpression),
+  Field(FieldExpression),
+  Error(TextSpan),
+  // An expression the parser expected but didn't find, the span is empty
+  Missing(TextSpan)
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct SyntaxTreeExpression {
-  pub kind: SyntaxTreeExpressionKind
+  pub kind: SyntaxTreeExpressionKind,
+  pub span: TextSpan
 }
 
 impl SyntaxTreeExpression {
-  pub fn new(kind: SyntaxTreeExpressionKind) -> Self {
-    SyntaxTreeExpression { kind }
+  pub fn new(kind: SyntaxTreeExpressionKind, span: TextSpan) -> Self {
+    SyntaxTreeExpression { kind, span }
+  }
+
+  pub fn number(number: Number, span: TextSpan) -> Self {
+    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Number(NumberExpression { number }), span);
   }
 
-  pub fn number(number: i64) -> Self {
-    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Number(NumberExpression { number }));
+  pub fn boolean(value: bool, span: TextSpan) -> Self {
+    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Boolean(BooleanExpression { value }), span);
   }
 
-  pub fn binary(operator: BinaryOperator, left: SyntaxTreeExpression, right: SyntaxTreeExpression) -> Self {
-    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Binary(BinaryExpression{ left: Box::new(left), operator, right: Box::new(right)}));
+  pub fn binary(operator: BinaryOperator, left: SyntaxTreeExpression, right: SyntaxTreeExpression, span: TextSpan) -> Self {
+    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Binary(BinaryExpression{ left: Box::new(left), operator, right: Box::new(right)}), span);
   }
 
-  pub fn parenthsised(expression: SyntaxTreeExpression) -> Self {
-    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Parenthesised(ParenthesisExpression {expression: Box::new(expression)}));
+  pub fn parenthsised(expression: SyntaxTreeExpression, span: TextSpan) -> Self {
+    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Parenthesised(ParenthesisExpression {expression: Box::new(expression)}), span);
   }
 
   pub fn identifier(identifier: Token) -> Self {
-    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Variable(VariableExpression { identifier }));
+    let span = identifier.span.clone();
+    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Variable(VariableExpression { identifier }), span);
+  }
+
+  pub fn string(value: String, span: TextSpan) -> Self {
+    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::String(StringExpression { value }), span);
+  }
+
+  pub fn interpolation(parts: Vec<InterpolationPart>, span: TextSpan) -> Self {
+    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Interpolation(InterpolationExpression { parts }), span);
+  }
+
+  pub fn block(statements: Vec<SyntaxTreeStatement>, span: TextSpan) -> Self {
+    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Block(BlockExpression { statements }), span);
+  }
+
+  pub fn if_expression(condition: SyntaxTreeExpression, then_branch: SyntaxTreeExpression, else_branch: Option<SyntaxTreeExpression>, span: TextSpan) -> Self {
+    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::If(IfExpression {
+      condition: Box::new(condition),
+      then_branch: Box::new(then_branch),
+      else_branch: else_branch.map(Box::new)
+    }), span);
+  }
+
+  pub fn assignment(target: SyntaxTreeExpression, operator: AssignmentOperator, value: SyntaxTreeExpression, span: TextSpan) -> Self {
+    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Assignment(AssignmentExpression {
+      target: Box::new(target),
+      operator,
+      value: Box::new(value)
+    }), span);
+  }
+
+  pub fn call(callee: SyntaxTreeExpression, arguments: Vec<SyntaxTreeExpression>, span: TextSpan) -> Self {
+    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Call(CallExpression { callee: Box::new(callee), arguments }), span);
+  }
+
+  pub fn closure(parameters: Vec<Token>, body: SyntaxTreeExpression, span: TextSpan) -> Self {
+    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Closure(ClosureExpression { parameters, body: Rc::new(body) }), span);
+  }
+
+  pub fn array(elements: Vec<SyntaxTreeExpression>, span: TextSpan) -> Self {
+    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Array(ArrayExpression { elements }), span);
+  }
+
+  pub fn index(target: SyntaxTreeExpression, index: SyntaxTreeExpression, span: TextSpan) -> Self {
+    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Index(IndexExpression { target: Box::new(target), index: Box::new(index) }), span);
+  }
+
+  pub fn map(entries: Vec<MapEntry>, span: TextSpan) -> Self {
+    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Map(MapExpression { entries }), span);
+  }
+
+  pub fn struct_literal(identifier: Token, initialisers: Vec<FieldInitialiser>, span: TextSpan) -> Self {
+    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::StructLiteral(StructLiteralExpression { identifier, initialisers }), span);
+  }
+
+  pub fn field(target: SyntaxTreeExpression, field: Token, span: TextSpan) -> Self {
+    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Field(FieldExpression { target: Box::new(target), field }), span);
   }
 
   pub fn error(span: TextSpan) -> Self {
-    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Error(span));
+    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Error(span.clone()), span);
+  }
+
+  pub fn missing(span: TextSpan) -> Self {
+    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Missing(span.clone()), span);
   }
 
-  pub fn unary(operator: UnaryOperator, operand: SyntaxTreeExpression) -> Self {
-    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Unary(UnaryExpression { operator, operand: Box::new(operand) }));
+  pub fn unary(operator: UnaryOperator, operand: SyntaxTreeExpression, span: TextSpan) -> Self {
+    return SyntaxTreeExpression::new(SyntaxTreeExpressionKind::Unary(UnaryExpression { operator, operand: Box::new(operand) }), span);
   }
-}
\ No newline at end of file
+}
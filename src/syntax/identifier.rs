@@ -0,0 +1,68 @@
+// Identifier rules following Unicode UAX #31: an identifier starts with an
+// XID_Start character or `_` and continues with XID_Continue characters.
+
+use unicode_normalization::UnicodeNormalization;
+use unicode_script::{Script, UnicodeScript};
+use unicode_xid::UnicodeXID;
+
+// Letters from other scripts that render like an ASCII letter
+const CONFUSABLES: &[(char, char)] = &[
+  // Cyrillic
+  ('а', 'a'), ('е', 'e'), ('о', 'o'), ('р', 'p'), ('с', 'c'), ('у', 'y'), ('х', 'x'),
+  ('і', 'i'), ('ј', 'j'), ('ѕ', 's'), ('ԁ', 'd'), ('ԛ', 'q'), ('ԝ', 'w'), ('һ', 'h'),
+  ('А', 'A'), ('В', 'B'), ('Е', 'E'), ('К', 'K'), ('М', 'M'), ('Н', 'H'), ('О', 'O'),
+  ('Р', 'P'), ('С', 'C'), ('Т', 'T'), ('Х', 'X'), ('І', 'I'), ('Ј', 'J'), ('Ѕ', 'S'),
+  // Greek
+  ('α', 'a'), ('ο', 'o'), ('ν', 'v'), ('ρ', 'p'), ('Α', 'A'), ('Β', 'B'), ('Ε', 'E'),
+  ('Ζ', 'Z'), ('Η', 'H'), ('Ι', 'I'), ('Κ', 'K'), ('Μ', 'M'), ('Ν', 'N'), ('Ο', 'O'),
+  ('Ρ', 'P'), ('Τ', 'T'), ('Υ', 'Y'), ('Χ', 'X')
+];
+
+// Script combinations UTS #39 considers "highly restrictive", mixing within one
+// of these groups is normal for e.g. Japanese identifiers.
+const SCRIPT_COMBINATIONS: &[&[Script]] = &[
+  &[Script::Latin, Script::Han, Script::Hiragana, Script::Katakana],
+  &[Script::Latin, Script::Han, Script::Bopomofo],
+  &[Script::Latin, Script::Han, Script::Hangul]
+];
+
+pub fn is_identifier_start(c: &char) -> bool {
+  return *c == '_' || c.is_xid_start();
+}
+
+pub fn is_identifier_continue(c: &char) -> bool {
+  return c.is_xid_continue();
+}
+
+// Identifiers are compared in NFC so that precomposed and decomposed spellings
+// of the same name refer to the same binding.
+pub fn normalise(identifier: &str) -> String {
+  return identifier.nfc().collect();
+}
+
+// Scripts used by an identifier in order of appearance. Digits, `_` and
+// combining marks belong to every script and are not counted.
+pub fn scripts(identifier: &str) -> Vec<Script> {
+  let mut scripts = Vec::new();
+  for c in identifier.chars() {
+    let script = c.script();
+    if script != Script::Common && script != Script::Inherited && !scripts.contains(&script) {
+      scripts.push(script);
+    }
+  }
+  return scripts;
+}
+
+pub fn is_mixed_script(scripts: &[Script]) -> bool {
+  return scripts.len() > 1 && !SCRIPT_COMBINATIONS.iter().any(
+    |combination| scripts.iter().all(|script| combination.contains(script))
+  );
+}
+
+// The identifier with every lookalike of an ASCII letter replaced by that
+// letter. Two identifiers with the same skeleton can be mistaken for each other.
+pub fn skeleton(identifier: &str) -> String {
+  return identifier.chars().map(|c| {
+    return CONFUSABLES.iter().find(|(confusable, _)| *confusable == c).map_or(c, |(_, ascii)| *ascii);
+  }).collect();
+}
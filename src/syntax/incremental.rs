@@ -0,0 +1,327 @@
+// Incremental relexing and reparsing for editors. An edit only relexes the
+// tokens around it and only reparses the statements whose tokens changed, the
+// rest of the previous token stream and syntax tree is reused, moved by the
+// length difference of the edit when it comes after it.
+
+use std::{cell::RefCell, ops::Range, rc::Rc};
+
+use crate::{
+  diagnostics::{Diagnostic, DiagnosticGlossary, DiagnosticGlossaryCell},
+  text::TextEdit
+};
+
+use super::{
+  expression::{SyntaxTreeExpression, SyntaxTreeExpressionKind},
+  lexer::{Lexer, Token, TokenType},
+  parser::Parser,
+  statement::{SyntaxTreeStatement, SyntaxTreeStatementKind},
+  types::_string::InterpolationPart,
+  SyntaxTree
+};
+
+pub struct Relex {
+  pub tokens: Vec<Token>,
+  // Indices of the tokens that were lexed again
+  pub relexed: Range<usize>
+}
+
+pub struct Reparse {
+  pub tokens: Vec<Token>,
+  pub tree: SyntaxTree,
+  pub relexed: Range<usize>,
+  // Indices of the statements that were parsed again
+  pub reparsed: Range<usize>
+}
+
+// Takes the raw token stream of the text before the edit. Only diagnostics of
+// the relexed tokens are reported, the others were reported before.
+pub fn relex(tokens: Vec<Token>, edit: &TextEdit, diagnostics_glossary: DiagnosticGlossaryCell) -> Relex {
+  let old_text = tokens.iter().map(|token| token.span.literal.as_str()).collect::<String>();
+  let text = edit.apply(&old_text);
+  let delta = edit.delta();
+  let edit_end = edit.range.start + edit.replacement.len();
+
+  // A token ending more than the lexer's lookahead before the edit was lexed
+  // without looking at the edited text, relexing starts at the first token
+  // that doesn't
+  let unchanged_end = old_text[..edit.range.start].char_indices().rev().nth(Lexer::MAX_LOOKAHEAD - 1).map_or(0, |(index, _)| index);
+  let start = tokens.iter().position(|token| token.span.end > unchanged_end).unwrap_or(tokens.len() - 1);
+
+  let mut old_tokens = tokens.into_iter().peekable();
+  let mut result: Vec<Token> = old_tokens.by_ref().take(start).collect();
+  let position = old_tokens.peek().map_or(0, |token| token.span.start);
+  let mut synchronised = false;
+  for token in Lexer::starting_at(&text, position, diagnostics_glossary) {
+    let (kind, end) = (token.kind.clone(), token.span.end);
+    result.push(token);
+    if kind == TokenType::Eof || end < edit_end {
+      continue;
+    }
+    // Past the edit the text is unchanged, so lexing on from a position where
+    // the old stream had a token would only produce the old tokens again
+    let old_position = end.wrapping_add_signed(-delta);
+    while old_tokens.next_if(|token| token.span.start < old_position).is_some() {}
+    if old_tokens.peek().is_some_and(|token| token.span.start == old_position) {
+      synchronised = true;
+      break;
+    }
+  }
+  let relexed = start..result.len();
+  if synchronised {
+    result.extend(old_tokens.map(|mut token| {
+      token.shift(delta);
+      token
+    }));
+  }
+  return Relex { tokens: result, relexed };
+}
+
+// Takes the raw token stream and syntax tree of the text before the edit and
+// returns the ones of the text after it, equal to lexing and parsing it anew.
+// The lexer and parser diagnostics of the text before the edit are reported
+// again for the reused statements, moved along with them, and the reparsed
+// statements report their own.
+pub fn reparse(
+  tokens: Vec<Token>, tree: SyntaxTree, diagnostics: &[Diagnostic], edit: &TextEdit, diagnostics_glossary: DiagnosticGlossaryCell
+) -> Reparse {
+  let delta = edit.delta();
+  let old_raw_tokens = tokens.clone();
+  let old_tokens = Token::attach_trivia(tokens.clone());
+  // The lexer diagnostics of the reparsed text are found by lexing it again
+  // below, relexing may have started after or stopped before it
+  let Relex { tokens, relexed } = relex(tokens, edit, Rc::new(RefCell::new(DiagnosticGlossary::new())));
+  let mut parser = Parser::new(tokens.clone(), Rc::clone(&diagnostics_glossary));
+
+  // Count the parser tokens, trivia and significant line breaks included, that
+  // are equal at the start of both streams, and at the end once moved by delta
+  let new_tokens = parser.tokens();
+  let prefix = old_tokens.iter().zip(new_tokens).take_while(|(old, new)| old == new).count();
+  let suffix = old_tokens.iter().rev().zip(new_tokens.iter().rev())
+    .take(old_tokens.len().min(new_tokens.len()) - prefix)
+    .take_while(|(old, new)| {
+      let mut old = (*old).clone();
+      old.shift(delta);
+      return old == **new;
+    })
+    .count();
+  let suffix_start = new_tokens.len() - suffix;
+
+  // A statement parses the same as long as its tokens are unchanged, up to the
+  // first token of the next statement which ends it
+  let eof = old_tokens[old_tokens.len() - 1].span.start;
+  let prefix_end = prefix.checked_sub(1).map(|index| old_tokens[index].span.start);
+  let mut statements = tree.statements;
+  let reused = (0..statements.len()).take_while(|index| {
+    let next = statements.get(index + 1).map_or(eof, |statement| statement.span.start);
+    return prefix_end.is_some_and(|prefix_end| next <= prefix_end);
+  }).count();
+  let last_reused = reused.checked_sub(1).map(|index| statements[index].span.start);
+  let mut old_statements = statements.split_off(reused).into_iter().peekable();
+  // Start of the reparsed text, which is before the edit and so in both texts
+  let reparse_start = if reused > 0 { old_statements.peek().map_or(eof, |statement| statement.span.start) } else { 0 };
+  if reused > 0 {
+    parser.seek(reparse_start);
+  }
+
+  // Parse until the next statement starts where an old one did, with the token
+  // before it, which the parser may look back at, unchanged
+  let mut synchronised = false;
+  // Start of the old statement before the one parsing synchronises at
+  let mut last_reparsed = last_reused;
+  while let Some(statement) = parser.next_statement() {
+    statements.push(statement);
+    parser.skip_terminators();
+    if parser.position() <= suffix_start {
+      continue;
+    }
+    let old_start = parser.tokens()[parser.position().min(parser.tokens().len() - 1)].span.start.wrapping_add_signed(-delta);
+    while let Some(statement) = old_statements.next_if(|statement| statement.span.start < old_start) {
+      last_reparsed = Some(statement.span.start);
+    }
+    if old_statements.peek().is_some_and(|statement| statement.span.start == old_start) {
+      synchronised = true;
+      break;
+    }
+  }
+  let reparsed = reused..statements.len();
+  // End of the reparsed text in the text before the edit
+  let reparse_end = match old_statements.peek() {
+    Some(statement) if synchronised => statement.span.start,
+    _ => usize::MAX
+  };
+  if synchronised {
+    statements.extend(old_statements.map(|mut statement| {
+      shift_statement(&mut statement, delta);
+      statement
+    }));
+  }
+
+  // A statement can report a diagnostic at the first token of the next one, so
+  // which of the two reported those at the ends of the reparsed text is found
+  // out by parsing the statement before each end again
+  let mut diagnostics_binding = diagnostics_glossary.borrow_mut();
+  let new_reparse_end = reparse_end.saturating_add_signed(delta);
+  let text = tokens.iter().map(|token| token.span.literal.as_str()).collect::<String>();
+  let lexer_diagnostics = Rc::new(RefCell::new(DiagnosticGlossary::new()));
+  Lexer::starting_at(&text, reparse_start, Rc::clone(&lexer_diagnostics))
+    .take_while(|token| token.span.start < new_reparse_end && token.kind != TokenType::Eof)
+    .for_each(drop);
+  for diagnostic in std::mem::take(&mut lexer_diagnostics.borrow_mut().diagnostics) {
+    if diagnostic.span.start < new_reparse_end {
+      diagnostics_binding.report(diagnostic);
+    }
+  }
+  if let Some(start) = last_reused {
+    for diagnostic in statement_diagnostics(old_raw_tokens.clone(), start) {
+      if diagnostic.span.start >= reparse_start {
+        diagnostics_binding.report(diagnostic);
+      }
+    }
+  }
+  let mut ending_reparsed = match last_reparsed {
+    Some(start) if synchronised => statement_diagnostics(old_raw_tokens, start),
+    _ => vec![]
+  };
+  for diagnostic in diagnostics {
+    if (reparse_start..reparse_end).contains(&diagnostic.span.start) {
+      continue;
+    }
+    if let Some(index) = ending_reparsed.iter().position(|reparsed| is_same_diagnostic(reparsed, diagnostic)) {
+      ending_reparsed.swap_remove(index);
+      continue;
+    }
+    diagnostics_binding.report(shift_diagnostic(diagnostic.clone(), edit));
+  }
+  return Reparse { tokens, tree: SyntaxTree { statements }, relexed, reparsed };
+}
+
+// Diagnostics that parsing the statement starting at an offset reports
+fn statement_diagnostics(tokens: Vec<Token>, start: usize) -> Vec<Diagnostic> {
+  let diagnostics_glossary = Rc::new(RefCell::new(DiagnosticGlossary::new()));
+  let mut parser = Parser::new(tokens, Rc::clone(&diagnostics_glossary));
+  parser.seek(start);
+  parser.next_statement();
+  return std::mem::take(&mut diagnostics_glossary.borrow_mut().diagnostics);
+}
+
+fn is_same_diagnostic(first: &Diagnostic, second: &Diagnostic) -> bool {
+  return first.message == second.message && first.span.start == second.span.start && first.span.end == second.span.end;
+}
+
+// Moves the spans of a diagnostic that come after the edit
+fn shift_diagnostic(mut diagnostic: Diagnostic, edit: &TextEdit) -> Diagnostic {
+  let spans = std::iter::once(&mut diagnostic.span).chain(diagnostic.labels.iter_mut().map(|label| &mut label.span));
+  for span in spans.filter(|span| span.start >= edit.range.end) {
+    span.shift(edit.delta());
+  }
+  return diagnostic;
+}
+
+fn shift_statement(statement: &mut SyntaxTreeStatement, delta: isize) {
+  statement.span.shift(delta);
+  match &mut statement.kind {
+    SyntaxTreeStatementKind::Expression(expr) => shift_expression(expr, delta),
+    SyntaxTreeStatementKind::Let(let_statement) => {
+      let_statement.identifier.shift(delta);
+      shift_expression(&mut let_statement.initialiser, delta);
+    }
+    SyntaxTreeStatementKind::Function(function) => {
+      function.identifier.shift(delta);
+      function.parameters.iter_mut().for_each(|parameter| parameter.shift(delta));
+      shift_expression(Rc::make_mut(&mut function.body), delta);
+    }
+    SyntaxTreeStatementKind::Return(return_statement) => {
+      return_statement.keyword.shift(delta);
+      if let Some(value) = &mut return_statement.value {
+        shift_expression(value, delta);
+      }
+    }
+    SyntaxTreeStatementKind::Struct(struct_declaration) => {
+      struct_declaration.identifier.shift(delta);
+      struct_declaration.fields.iter_mut().for_each(|field| field.shift(delta));
+    }
+    SyntaxTreeStatementKind::While(while_statement) => {
+      shift_expression(&mut while_statement.condition, delta);
+      shift_expression(&mut while_statement.body, delta);
+    }
+    SyntaxTreeStatementKind::For(for_statement) => {
+      for_statement.identifier.shift(delta);
+      shift_expression(&mut for_statement.iterable, delta);
+      shift_expression(&mut for_statement.body, delta);
+    }
+    SyntaxTreeStatementKind::Break(keyword) | SyntaxTreeStatementKind::Continue(keyword) => keyword.shift(delta)
+  }
+}
+
+fn shift_expression(expression: &mut SyntaxTreeExpression, delta: isize) {
+  expression.span.shift(delta);
+  match &mut expression.kind {
+    SyntaxTreeExpressionKind::Number(_) | SyntaxTreeExpressionKind::Boolean(_) | SyntaxTreeExpressionKind::String(_) => {}
+    SyntaxTreeExpressionKind::Binary(expr) => {
+      shift_expression(&mut expr.left, delta);
+      expr.operator.token.shift(delta);
+      shift_expression(&mut expr.right, delta);
+    }
+    SyntaxTreeExpressionKind::Assignment(expr) => {
+      shift_expression(&mut expr.target, delta);
+      expr.operator.token.shift(delta);
+      shift_expression(&mut expr.value, delta);
+    }
+    SyntaxTreeExpressionKind::Call(expr) => {
+      shift_expression(&mut expr.callee, delta);
+      expr.arguments.iter_mut().for_each(|argument| shift_expression(argument, delta));
+    }
+    SyntaxTreeExpressionKind::Array(expr) => expr.elements.iter_mut().for_each(|element| shift_expression(element, delta)),
+    SyntaxTreeExpressionKind::Map(expr) => {
+      for entry in &mut expr.entries {
+        shift_expression(&mut entry.key, delta);
+        shift_expression(&mut entry.value, delta);
+      }
+    }
+    SyntaxTreeExpressionKind::StructLiteral(expr) => {
+      expr.identifier.shift(delta);
+      for initialiser in &mut expr.initialisers {
+        initialiser.field.shift(delta);
+        shift_expression(&mut initialiser.value, delta);
+      }
+    }
+    SyntaxTreeExpressionKind::Field(expr) => {
+      shift_expression(&mut expr.target, delta);
+      expr.field.shift(delta);
+    }
+    SyntaxTreeExpressionKind::Index(expr) => {
+      shift_expression(&mut expr.target, delta);
+      shift_expression(&mut expr.index, delta);
+    }
+    SyntaxTreeExpressionKind::Closure(expr) => {
+      expr.parameters.iter_mut().for_each(|parameter| parameter.shift(delta));
+      shift_expression(Rc::make_mut(&mut expr.body), delta);
+    }
+    SyntaxTreeExpressionKind::Unary(expr) => {
+      expr.operator.token.shift(delta);
+      shift_expression(&mut expr.operand, delta);
+    }
+    SyntaxTreeExpressionKind::Parenthesised(expr) => shift_expression(&mut expr.expression, delta),
+    SyntaxTreeExpressionKind::Block(expr) => {
+      for statement in &mut expr.statements {
+        shift_statement(statement, delta);
+      }
+    }
+    SyntaxTreeExpressionKind::If(expr) => {
+      shift_expression(&mut expr.condition, delta);
+      shift_expression(&mut expr.then_branch, delta);
+      if let Some(else_branch) = &mut expr.else_branch {
+        shift_expression(else_branch, delta);
+      }
+    }
+    SyntaxTreeExpressionKind::Variable(expr) => expr.identifier.shift(delta),
+    SyntaxTreeExpressionKind::Interpolation(expr) => {
+      for part in &mut expr.parts {
+        if let InterpolationPart::Expression(expr) = part {
+          shift_expression(expr, delta);
+        }
+      }
+    }
+    SyntaxTreeExpressionKind::Error(span) | SyntaxTreeExpressionKind::Missing(span) => span.shift(delta)
+  }
+}
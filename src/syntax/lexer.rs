@@ -1,10 +1,31 @@
 // Lexical Analyser for Unilang
 
-use std::fmt::{Display, Formatter, write};
+use std::{fmt::{Display, Formatter}, str::CharIndices};
+
+use num_bigint::BigInt;
+
+use crate::diagnostics::DiagnosticGlossaryCell;
+
+use super::identifier;
+
+// Characters that start a punctuation or operator token
+const PUNCTUATION: &str = "+-*/%=!<>&|^~:.,;(){}[]\\";
+
+// Lookalikes of ASCII punctuation commonly pasted from documents and word processors
+const CHARACTER_HINTS: &[(char, &str)] = &[
+  ('\u{201C}', "\""), ('\u{201D}', "\""), ('\u{201E}', "\""), ('\u{2033}', "\""),
+  ('\u{2018}', "\""), ('\u{2019}', "\""),
+  ('\u{2260}', "!="), ('\u{2264}', "<="), ('\u{2265}', ">="),
+  ('\u{2212}', "-"), ('\u{2013}', "-"), ('\u{2014}', "-"),
+  ('\u{00D7}', "*"), ('\u{00F7}', "/"), ('\u{2026}', ".."), ('\u{037E}', ";")
+];
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum TokenType {
   Number(i64),
+  Float(f64),
+  BigInteger(BigInt),
+  String(Vec<StringSegment>),
   // Operators
   Plus,
   Minus,
@@ -16,16 +37,64 @@ pub enum TokenType {
   Caret,
   DoubleAsterisk,
   Tilde,
+  Percent,
+  Bang,
+  DoubleEqual,
+  BangEqual,
+  LessThan,
+  LessThanEqual,
+  GreaterThan,
+  GreaterThanEqual,
+  DoubleAmpersand,
+  DoublePipe,
+  DoubleLessThan,
+  DoubleGreaterThan,
+  PlusEqual,
+  MinusEqual,
+  AsteriskEqual,
+  ForwardSlashEqual,
+  Arrow,
+  FatArrow,
+  DoubleColon,
+  DoubleDot,
+  DoubleDotEqual,
+  Dot,
   // Other
   LeftParenthesis,
   RightParenthesis,  
+  LeftBrace,
+  RightBrace,
+  LeftBracket,
+  RightBracket,
+  Comma,
+  SemiColon,
+  Colon,
   BadChar,
+  // A number literal out of range, reported by the lexer and without a value
+  BadNumber,
   BackSlash,
   WhiteSpace,
+  NewLine,
+  LineComment,
+  BlockComment,
   Identifier,
   Eof,
   // Keywords
   Let,
+  True,
+  False,
+  If,
+  Else,
+  While,
+  Break,
+  Continue,
+  For,
+  In,
+  Step,
+  Mut,
+  Fn,
+  Return,
+  Struct,
   // TODO: Add more token types to assess in the parser
 
 }
@@ -34,6 +103,9 @@ impl Display for TokenType {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
     match self {
       TokenType::Number(_) => write!(f, "Number"),
+      TokenType::Float(_) => write!(f, "Float"),
+      TokenType::BigInteger(_) => write!(f, "BigInteger"),
+      TokenType::String(_) => write!(f, "String"),
       TokenType::Plus => write!(f, "+"),
       TokenType::Minus => write!(f, "-"),
       TokenType::Asterisk => write!(f, "*"),
@@ -41,18 +113,64 @@ impl Display for TokenType {
       TokenType::BackSlash => write!(f, "\\"),
       TokenType::LeftParenthesis => write!(f, "("),
       TokenType::RightParenthesis => write!(f, ")"),
-      TokenType::BackSlash => write!(f, "\\"),
       TokenType::BadChar => write!(f, "Bad"),
+      TokenType::BadNumber => write!(f, "BadNumber"),
       TokenType::WhiteSpace => write!(f, "Whitespace"),
+      TokenType::NewLine => write!(f, "NewLine"),
+      TokenType::LineComment => write!(f, "LineComment"),
+      TokenType::BlockComment => write!(f, "BlockComment"),
       TokenType::Eof => write!(f, "EOF"),
       TokenType::Identifier => write!(f, "Identifier"),
       TokenType::Equal => write!(f, "="),
       TokenType::Let => write!(f, "Let"),
+      TokenType::True => write!(f, "True"),
+      TokenType::False => write!(f, "False"),
+      TokenType::If => write!(f, "If"),
+      TokenType::Else => write!(f, "Else"),
+      TokenType::While => write!(f, "While"),
+      TokenType::Break => write!(f, "Break"),
+      TokenType::Continue => write!(f, "Continue"),
+      TokenType::For => write!(f, "For"),
+      TokenType::In => write!(f, "In"),
+      TokenType::Step => write!(f, "Step"),
+      TokenType::Mut => write!(f, "Mut"),
+      TokenType::Fn => write!(f, "Fn"),
+      TokenType::Return => write!(f, "Return"),
+      TokenType::Struct => write!(f, "Struct"),
       TokenType::Ampersand => write!(f, "&"),
       TokenType::Pipe => write!(f, "|"),
       TokenType::Caret => write!(f, "^"),
       TokenType::DoubleAsterisk => write!(f, "**"),
-      TokenType::Tilde => write!(f, "~")
+      TokenType::Tilde => write!(f, "~"),
+      TokenType::Percent => write!(f, "%"),
+      TokenType::Bang => write!(f, "!"),
+      TokenType::DoubleEqual => write!(f, "=="),
+      TokenType::BangEqual => write!(f, "!="),
+      TokenType::LessThan => write!(f, "<"),
+      TokenType::LessThanEqual => write!(f, "<="),
+      TokenType::GreaterThan => write!(f, ">"),
+      TokenType::GreaterThanEqual => write!(f, ">="),
+      TokenType::DoubleAmpersand => write!(f, "&&"),
+      TokenType::DoublePipe => write!(f, "||"),
+      TokenType::DoubleLessThan => write!(f, "<<"),
+      TokenType::DoubleGreaterThan => write!(f, ">>"),
+      TokenType::PlusEqual => write!(f, "+="),
+      TokenType::MinusEqual => write!(f, "-="),
+      TokenType::AsteriskEqual => write!(f, "*="),
+      TokenType::ForwardSlashEqual => write!(f, "/="),
+      TokenType::Arrow => write!(f, "->"),
+      TokenType::FatArrow => write!(f, "=>"),
+      TokenType::DoubleColon => write!(f, "::"),
+      TokenType::DoubleDot => write!(f, ".."),
+      TokenType::DoubleDotEqual => write!(f, "..="),
+      TokenType::Dot => write!(f, "."),
+      TokenType::LeftBrace => write!(f, "{{"),
+      TokenType::RightBrace => write!(f, "}}"),
+      TokenType::LeftBracket => write!(f, "["),
+      TokenType::RightBracket => write!(f, "]"),
+      TokenType::Comma => write!(f, ","),
+      TokenType::SemiColon => write!(f, ";"),
+      TokenType::Colon => write!(f, ":")
     }
   }
 }
@@ -68,12 +186,29 @@ pub struct TextSpan {
 #[derive(Debug, PartialEq, Clone)]
 pub struct Token {
   pub(crate) kind: TokenType,
-  pub(crate) span: TextSpan
+  pub(crate) span: TextSpan,
+  // Whitespace and comments surrounding the token. Trailing trivia runs up to and
+  // including the end of the token's line, everything after that leads the next token.
+  pub(crate) leading_trivia: Vec<Token>,
+  pub(crate) trailing_trivia: Vec<Token>
 }
 
+// A string literal is split into plain text and the tokens of each `${...}`
+// interpolation, the parser turns the latter into expressions.
+#[derive(Debug, PartialEq, Clone)]
+pub enum StringSegment {
+  Text(String),
+  Interpolation(Vec<Token>)
+}
+
+// The lexer walks the input once through a CharIndices cursor. current_position
+// is always the byte offset of the cursor so spans slice the input directly.
 pub struct Lexer<'a> {
   input: &'a str,
-  current_position: usize
+  chars: CharIndices<'a>,
+  current_position: usize,
+  finished: bool,
+  diagnostics_glossary: DiagnosticGlossaryCell
 }
 
 // Interfaces End Here
@@ -86,28 +221,146 @@ impl TextSpan {
   pub fn length(&self) -> usize {
     return self.end - self.start;
   }
+
+  pub fn shift(&mut self, delta: isize) {
+    self.start = self.start.wrapping_add_signed(delta);
+    self.end = self.end.wrapping_add_signed(delta);
+  }
 }
 
 impl Token {
   pub fn new(kind: TokenType, span: TextSpan) -> Self {
-    return Self { kind, span };
+    return Self { kind, span, leading_trivia: Vec::new(), trailing_trivia: Vec::new() };
   }
-}
 
-impl <'a> Lexer<'a> {
+  // Stands in for a token the parser expected but didn't find
+  pub fn missing(kind: TokenType, position: usize) -> Self {
+    return Self::new(kind, TextSpan::new(position, position, String::new()));
+  }
+
+  // Every token in the source covers at least one byte, except for EOF
+  pub fn is_missing(&self) -> bool {
+    return self.span.length() == 0 && self.kind != TokenType::Eof;
+  }
+
+  // Keywords that can only start a statement, the parser resumes at them after an error
+  pub fn starts_statement(&self) -> bool {
+    return matches!(self.kind,
+      TokenType::Let | TokenType::Fn | TokenType::Struct | TokenType::While | TokenType::For | TokenType::Break | TokenType::Continue |
+      TokenType::Return
+    );
+  }
+
+  // Moves the token, its trivia and interpolated tokens by delta bytes
+  pub fn shift(&mut self, delta: isize) {
+    self.span.shift(delta);
+    for trivia in self.leading_trivia.iter_mut().chain(self.trailing_trivia.iter_mut()) {
+      trivia.shift(delta);
+    }
+    if let TokenType::String(segments) = &mut self.kind {
+      for segment in segments {
+        if let StringSegment::Interpolation(tokens) = segment {
+          tokens.iter_mut().for_each(|token| token.shift(delta));
+        }
+      }
+    }
+  }
+
+  // Bad characters are reported by the lexer and skipped over by the parser
+  pub fn is_trivia(&self) -> bool {
+    return matches!(self.kind, TokenType::WhiteSpace | TokenType::LineComment | TokenType::BlockComment | TokenType::BadChar);
+  }
+
+  // A token after which the statement obviously goes on, so a line break following it is not a terminator
+  fn continues_line(&self) -> bool {
+    return matches!(self.kind,
+      TokenType::NewLine | TokenType::SemiColon | TokenType::Comma | TokenType::Colon | TokenType::DoubleColon |
+      TokenType::LeftParenthesis | TokenType::LeftBrace | TokenType::LeftBracket |
+      TokenType::Plus | TokenType::Minus | TokenType::Asterisk | TokenType::ForwardSlash | TokenType::Percent |
+      TokenType::DoubleAsterisk | TokenType::Ampersand | TokenType::Pipe | TokenType::Caret | TokenType::Tilde |
+      TokenType::Bang | TokenType::DoubleEqual | TokenType::BangEqual | TokenType::LessThan | TokenType::LessThanEqual |
+      TokenType::GreaterThan | TokenType::GreaterThanEqual | TokenType::DoubleAmpersand | TokenType::DoublePipe |
+      TokenType::DoubleLessThan | TokenType::DoubleGreaterThan | TokenType::Equal | TokenType::PlusEqual |
+      TokenType::MinusEqual | TokenType::AsteriskEqual | TokenType::ForwardSlashEqual | TokenType::Arrow |
+      TokenType::FatArrow | TokenType::Dot | TokenType::DoubleDot | TokenType::DoubleDotEqual | TokenType::In |
+      TokenType::Step
+    );
+  }
 
-  pub fn new(input: &'a str) -> Self {
-    Self { input, current_position: 0 }
+  // A line break terminates a statement, unless it is inside parentheses or
+  // brackets, or the line ends in a token that needs a continuation.
+  fn is_terminator(&self, previous: Option<&Token>, delimiters: &[TokenType]) -> bool {
+    if self.kind != TokenType::NewLine {
+      return false;
+    }
+    if matches!(delimiters.last(), Some(TokenType::LeftParenthesis) | Some(TokenType::LeftBracket)) {
+      return false;
+    }
+    return previous.is_some_and(|previous| !previous.continues_line());
+  }
+
+  // Folds the trivia tokens of a raw token stream into the neighbouring tokens.
+  // Line breaks that don't terminate a statement are folded in as trivia too.
+  pub fn attach_trivia(tokens: Vec<Token>) -> Vec<Token> {
+    let mut result: Vec<Token> = Vec::new();
+    let mut leading_trivia = Vec::new();
+    let mut trailing = false;
+    let mut delimiters = Vec::new();
+    for token in tokens {
+      let is_trivia = token.is_trivia() || (token.kind == TokenType::NewLine && !token.is_terminator(result.last(), &delimiters));
+      if !is_trivia {
+        // A statement can't be inside parentheses or brackets, they were left unclosed
+        if token.starts_statement() {
+          while matches!(delimiters.last(), Some(TokenType::LeftParenthesis) | Some(TokenType::LeftBracket)) {
+            delimiters.pop();
+          }
+        }
+        match token.kind {
+          TokenType::LeftParenthesis | TokenType::LeftBracket | TokenType::LeftBrace => delimiters.push(token.kind.clone()),
+          TokenType::RightParenthesis | TokenType::RightBracket | TokenType::RightBrace => {
+            delimiters.pop();
+          }
+          _ => {}
+        }
+        let mut token = token;
+        token.leading_trivia = std::mem::take(&mut leading_trivia);
+        trailing = token.kind != TokenType::NewLine;
+        result.push(token);
+        continue;
+      }
+      if trailing {
+        let ends_line = token.kind == TokenType::NewLine;
+        result.last_mut().unwrap().trailing_trivia.push(token);
+        trailing = !ends_line;
+      }
+      else {
+        leading_trivia.push(token);
+      }
+    }
+    // Trivia after the last token only happens for streams without an EOF token
+    if let Some(last) = result.last_mut() {
+      last.trailing_trivia.append(&mut leading_trivia);
+    }
+    return result;
   }
+}
+
+// Every byte of the input belongs to exactly one token, trivia included, and
+// the stream always ends with a single EOF token.
+impl <'a> Iterator for Lexer<'a> {
+  type Item = Token;
 
-  pub fn next_token(&mut self) -> Option<Token> {
-    // Check if the current character is the end of file
+  fn next(&mut self) -> Option<Token> {
+    // Check if the current character is the end of file. EOF is an empty token
+    // at the end of input, so token literals concatenate back to the source.
     if self.current_position == self.input.len() {
-      let _eof: char = '\0';
-      self.current_position += 1;
+      if self.finished {
+        return None;
+      }
+      self.finished = true;
       return Some(Token::new(
         TokenType::Eof,
-        TextSpan::new(0, 0, _eof.to_string())
+        TextSpan::new(self.current_position, self.current_position, String::new())
       ));
     }
 
@@ -115,24 +368,57 @@ impl <'a> Lexer<'a> {
     return c.map(|c| {
       let start = self.current_position;
       // println!("Start: {}", start);
-      let mut kind = TokenType::BadChar;
+      let kind;
       
       if Self::is_number_start(&c) {
         // println!("Consuming Number: {}", c);
-        let number: i64 = self.consume_number();
-        kind = TokenType::Number(number);
+        kind = self.consume_number();
+      }
+      else if c == '\n' || (c == '\r' && self.peek(1) == Some('\n')) {
+        self.consume_if('\r');
+        self.consume();
+        kind = TokenType::NewLine;
       }
       else if Self::is_whitespace(&c){
         // println!("Consuming Whitespace");
-        self.consume();
+        self.consume_whitespace();
         kind = TokenType::WhiteSpace
       }
-      else if Self::is_identifier_start(&c) {
+      else if c == '/' && self.peek(1) == Some('/') {
+        self.consume_line_comment();
+        kind = TokenType::LineComment;
+      }
+      else if c == '/' && self.peek(1) == Some('*') {
+        self.consume_block_comment();
+        kind = TokenType::BlockComment;
+      }
+      else if c == '"' {
+        kind = self.consume_string();
+      }
+      else if identifier::is_identifier_start(&c) {
         let identifier = self.consume_identifier();
         kind = match identifier.as_str() {
           "let" => TokenType::Let,
+          "true" => TokenType::True,
+          "false" => TokenType::False,
+          "if" => TokenType::If,
+          "else" => TokenType::Else,
+          "while" => TokenType::While,
+          "break" => TokenType::Break,
+          "continue" => TokenType::Continue,
+          "for" => TokenType::For,
+          "in" => TokenType::In,
+          "step" => TokenType::Step,
+          "mut" => TokenType::Mut,
+          "fn" => TokenType::Fn,
+          "return" => TokenType::Return,
+          "struct" => TokenType::Struct,
           _ => TokenType::Identifier
-        }
+        };
+      }
+      else if Self::is_bad_character(&c) {
+        self.consume_bad_characters();
+        kind = TokenType::BadChar;
       }
       else {
         kind = self.consume_punctuation();
@@ -143,96 +429,442 @@ impl <'a> Lexer<'a> {
       // println!("End: {}", end);      
       let literal = self.input[start..end].to_string();
       let span = TextSpan::new(start, end, literal);
+      if kind == TokenType::Identifier {
+        self.check_identifier(&span);
+      }
+      if kind == TokenType::BadChar {
+        self.report_bad_characters(&span);
+      }
       return Token::new(kind, span);
     })
     
   }
+}
 
-  fn is_number_start(c: &char) -> bool {
-    return c.is_digit(10)
+impl <'a> Lexer<'a> {
+
+  pub fn new(input: &'a str, diagnostics_glossary: DiagnosticGlossaryCell) -> Self {
+    Self { input, chars: input.char_indices(), current_position: 0, finished: false, diagnostics_glossary }
   }
 
-  fn is_identifier_start(c: &char) -> bool {
-    return c.is_alphabetic();
+  // Lexing a token only looks ahead, so lexing can resume at any token boundary
+  pub fn starting_at(input: &'a str, position: usize, diagnostics_glossary: DiagnosticGlossaryCell) -> Self {
+    Self { input, chars: input[position..].char_indices(), current_position: position, finished: false, diagnostics_glossary }
+  }
+
+  // Characters past the end of a token the lexer may look at to decide it,
+  // "1e+5" is only a float once the digit after the sign is seen
+  pub const MAX_LOOKAHEAD: usize = 3;
+
+  fn is_number_start(c: &char) -> bool {
+    return c.is_ascii_digit()
   }
 
   fn is_whitespace(c: &char) -> bool {
     return c.is_whitespace()
   }
 
+  fn is_bad_character(c: &char) -> bool {
+    return !(Self::is_number_start(c) || Self::is_whitespace(c) || identifier::is_identifier_start(c) || *c == '"' || PUNCTUATION.contains(*c));
+  }
+
+  fn character_hint(c: char) -> Option<&'static str> {
+    return CHARACTER_HINTS.iter().find(|(lookalike, _)| *lookalike == c).map(|(_, hint)| *hint);
+  }
+
+  // Consecutive bad characters become one token so they are reported once.
+  // Characters with a hint stay on their own to report the hint.
+  fn consume_bad_characters(&mut self) {
+    let first = self.consume().unwrap();
+    if Self::character_hint(first).is_some() {
+      return;
+    }
+    while let Some(c) = self.current() {
+      if !Self::is_bad_character(&c) || Self::character_hint(c).is_some() {
+        break;
+      }
+      self.consume();
+    }
+  }
+
+  fn report_bad_characters(&self, span: &TextSpan) {
+    let mut chars = span.literal.chars();
+    let mut diagnostics_binding = self.diagnostics_glossary.borrow_mut();
+    match (chars.next(), chars.next()) {
+      (Some(c), None) => diagnostics_binding.report_unexpected_character(span.clone(), c, Self::character_hint(c)),
+      _ => diagnostics_binding.report_unexpected_characters(span.clone())
+    }
+  }
+
   fn consume_punctuation(&mut self) -> TokenType {
     let c = self.consume().unwrap(); 
     
     // Debug Logs     
     // println!("Consuming Punctuation: {:?}", c);
 
+    // Longest match wins, "<<" is never lexed as two "<" tokens
     return match c {
+      '+' if self.consume_if('=') => TokenType::PlusEqual,
       '+' => TokenType::Plus,
+      '-' if self.consume_if('=') => TokenType::MinusEqual,
+      '-' if self.consume_if('>') => TokenType::Arrow,
       '-' => TokenType::Minus,
-      '*' => {
-        if let Some(next) = self.current() {
-          if next == '*' {
-            self.consume();
-            TokenType::DoubleAsterisk
-          }
-          else {
-            TokenType::Asterisk
-          }
-        } else {
-          TokenType::Asterisk
-        }
-      },
+      '*' if self.consume_if('*') => TokenType::DoubleAsterisk,
+      '*' if self.consume_if('=') => TokenType::AsteriskEqual,
+      '*' => TokenType::Asterisk,
+      '/' if self.consume_if('=') => TokenType::ForwardSlashEqual,
       '/' => TokenType::ForwardSlash,
-      '(' => TokenType::LeftParenthesis,
-      ')' => TokenType::RightParenthesis,
+      '%' => TokenType::Percent,
+      '=' if self.consume_if('=') => TokenType::DoubleEqual,
+      '=' if self.consume_if('>') => TokenType::FatArrow,
       '=' => TokenType::Equal,
-      '\\' => TokenType::BackSlash,
+      '!' if self.consume_if('=') => TokenType::BangEqual,
+      '!' => TokenType::Bang,
+      '<' if self.consume_if('=') => TokenType::LessThanEqual,
+      '<' if self.consume_if('<') => TokenType::DoubleLessThan,
+      '<' => TokenType::LessThan,
+      '>' if self.consume_if('=') => TokenType::GreaterThanEqual,
+      '>' if self.consume_if('>') => TokenType::DoubleGreaterThan,
+      '>' => TokenType::GreaterThan,
+      '&' if self.consume_if('&') => TokenType::DoubleAmpersand,
       '&' => TokenType::Ampersand,
+      '|' if self.consume_if('|') => TokenType::DoublePipe,
       '|' => TokenType::Pipe,
+      ':' if self.consume_if(':') => TokenType::DoubleColon,
+      ':' => TokenType::Colon,
+      '.' if self.consume_if('.') => {
+        if self.consume_if('=') { TokenType::DoubleDotEqual } else { TokenType::DoubleDot }
+      }
+      '.' => TokenType::Dot,
+      '(' => TokenType::LeftParenthesis,
+      ')' => TokenType::RightParenthesis,
+      '{' => TokenType::LeftBrace,
+      '}' => TokenType::RightBrace,
+      '[' => TokenType::LeftBracket,
+      ']' => TokenType::RightBracket,
+      ',' => TokenType::Comma,
+      ';' => TokenType::SemiColon,
+      '\\' => TokenType::BackSlash,
       '^' => TokenType::Caret,
       '~' => TokenType::Tilde,
       _ => TokenType::BadChar
     };
   }
 
+  fn consume_if(&mut self, expected: char) -> bool {
+    if self.current() == Some(expected) {
+      self.consume();
+      return true;
+    }
+    return false;
+  }
+
   fn consume_identifier(&mut self) -> String {
-    let mut identifier = String::new();
+    let start = self.current_position;
+    self.consume();
     while let Some(c) = self.current() {
-      if Self::is_identifier_start(&c) {
-        self.consume().unwrap();
-        identifier.push(c)
+      if identifier::is_identifier_continue(&c) {
+        self.consume();
       }
       else {
         break;
       }
     }
-    return identifier;
+    return self.input[start..self.current_position].to_string();
+  }
+
+  // Identifiers mixing scripts are legal but almost always a mistake or a
+  // spoofing attempt. Names that only look alike are found by the SymbolChecker.
+  fn check_identifier(&self, span: &TextSpan) {
+    let scripts = identifier::scripts(&span.literal);
+    if identifier::is_mixed_script(&scripts) {
+      let names: Vec<&str> = scripts.iter().map(|script| script.full_name()).collect();
+      self.diagnostics_glossary.borrow_mut().report_mixed_script_identifier(span.clone(), &names);
+    }
+  }
+
+  // Line breaks are tokens of their own, a whitespace run stops before them
+  fn consume_whitespace(&mut self) {
+    while let Some(c) = self.current() {
+      if !Self::is_whitespace(&c) || c == '\n' || (c == '\r' && self.peek(1) == Some('\n')) {
+        break;
+      }
+      self.consume();
+    }
+  }
+
+  fn consume_line_comment(&mut self) {
+    while let Some(c) = self.current() {
+      if c == '\n' {
+        break;
+      }
+      self.consume();
+    }
+  }
+
+  // Block comments nest, so `/* a /* b */ c */` is a single comment
+  fn consume_block_comment(&mut self) {
+    let start = self.current_position;
+    let mut depth = 0;
+    loop {
+      match (self.current(), self.peek(1)) {
+        (Some('/'), Some('*')) => {
+          self.consume();
+          depth += 1;
+        }
+        (Some('*'), Some('/')) => {
+          self.consume();
+          depth -= 1;
+          if depth == 0 {
+            self.consume();
+            return;
+          }
+        }
+        (None, _) => {
+          let span = self.span(start, start + 2);
+          self.diagnostics_glossary.borrow_mut().report_unterminated_block_comment(span);
+          return;
+        }
+        _ => {}
+      }
+      self.consume();
+    }
+  }
+
+  fn consume_string(&mut self) -> TokenType {
+    let start = self.current_position;
+    self.consume();
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    loop {
+      match self.current() {
+        None => {
+          let span = self.span(start, start + 1);
+          self.diagnostics_glossary.borrow_mut().report_unterminated_string(span);
+          break;
+        }
+        Some('"') => {
+          self.consume();
+          break;
+        }
+        Some('\\') => {
+          if let Some(c) = self.consume_escape_sequence() {
+            text.push(c);
+          }
+        }
+        Some('$') if self.peek(1) == Some('{') => {
+          if !text.is_empty() {
+            segments.push(StringSegment::Text(std::mem::take(&mut text)));
+          }
+          segments.push(StringSegment::Interpolation(self.consume_interpolation()));
+          // An interpolation running into the end of input has already been
+          // reported, the enclosing string is unterminated as a consequence.
+          if self.current().is_none() {
+            break;
+          }
+        }
+        Some(c) => {
+          self.consume();
+          text.push(c);
+        }
+      }
+    }
+    if !text.is_empty() || segments.is_empty() {
+      segments.push(StringSegment::Text(text));
+    }
+    return TokenType::String(segments);
+  }
+
+  fn consume_escape_sequence(&mut self) -> Option<char> {
+    let start = self.current_position;
+    self.consume();
+    let escaped = match self.consume() {
+      Some('n') => '\n',
+      Some('t') => '\t',
+      Some('r') => '\r',
+      Some('0') => '\0',
+      Some('\\') => '\\',
+      Some('"') => '"',
+      Some('$') => '$',
+      Some('u') => return self.consume_unicode_escape(start),
+      Some(_) => {
+        let span = self.span(start, self.current_position);
+        self.diagnostics_glossary.borrow_mut().report_unknown_escape_sequence(span);
+        return None;
+      }
+      None => return None
+    };
+    return Some(escaped);
+  }
+
+  // \u{1F600}: one to six hex digits between braces naming a Unicode scalar value
+  fn consume_unicode_escape(&mut self, start: usize) -> Option<char> {
+    let mut digits = String::new();
+    let mut terminated = false;
+    if self.current() == Some('{') {
+      self.consume();
+      while let Some(c) = self.current() {
+        if c == '}' {
+          self.consume();
+          terminated = true;
+          break;
+        }
+        if !c.is_ascii_hexdigit() {
+          break;
+        }
+        self.consume();
+        digits.push(c);
+      }
+    }
+    let c = u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32);
+    if !terminated || digits.len() > 6 || c.is_none() {
+      let span = self.span(start, self.current_position);
+      self.diagnostics_glossary.borrow_mut().report_invalid_unicode_escape(span);
+      return None;
+    }
+    return c;
+  }
+
+  // Lexes the tokens of a `${...}` interpolation up to its matching brace. The
+  // returned tokens end with an EOF token placed on the closing brace.
+  fn consume_interpolation(&mut self) -> Vec<Token> {
+    let start = self.current_position;
+    self.consume();
+    self.consume();
+    let mut tokens = Vec::new();
+    let mut depth = 0;
+    loop {
+      match self.current() {
+        None => {
+          let span = self.span(start, start + 2);
+          self.diagnostics_glossary.borrow_mut().report_unterminated_interpolation(span);
+          break;
+        }
+        Some('}') if depth == 0 => break,
+        Some('{') => depth += 1,
+        Some('}') => depth -= 1,
+        _ => {}
+      }
+      if let Some(token) = self.next() {
+        tokens.push(token);
+      }
+    }
+    let end = self.current_position;
+    tokens.push(Token::new(TokenType::Eof, TextSpan::new(end, end, String::new())));
+    self.consume();
+    return tokens;
+  }
+
+  fn span(&self, start: usize, end: usize) -> TextSpan {
+    return TextSpan::new(start, end, self.input[start..end].to_string());
   }
 
   fn current(&self) -> Option<char> {
-    return self.input.chars().nth(self.current_position);
+    return self.peek(0);
   }
 
   fn consume(&mut self) -> Option<char> {
-    if self.current_position >= self.input.len() {
-       return None;
+    let (_, c) = self.chars.next()?;
+    self.current_position += c.len_utf8();
+    return Some(c);
+  }
+
+  // Lookahead clones the cursor, which is cheap and never rescans consumed input
+  fn peek(&self, offset: usize) -> Option<char> {
+    return self.chars.clone().nth(offset).map(|(_, c)| c);
+  }
+
+  // Numeric literals come in three shapes:
+  //   - Radix prefixed integers: 0xFF, 0b1010, 0o17
+  //   - Decimal integers: 42, 1_000_000
+  //   - Decimal floats: 3.14, 1e-9, 2.5E+3
+  // Underscores are accepted as digit separators anywhere after the first digit.
+  // Integers with an `n` suffix (12n, 0xFFn) are arbitrary-precision integers.
+  fn consume_number(&mut self) -> TokenType {
+    let start = self.current_position;
+    if self.current() == Some('0') {
+      let radix = match self.peek(1) {
+        Some('x') | Some('X') => Some(16),
+        Some('o') | Some('O') => Some(8),
+        Some('b') | Some('B') => Some(2),
+        _ => None
+      };
+      // A prefix is only taken when a digit of that radix follows it, otherwise
+      // "0x" lexes as the number 0 followed by the identifier x.
+      if let Some(radix) = radix.filter(|radix| self.peek(2).is_some_and(|c| c.is_digit(*radix))) {
+        self.consume();
+        self.consume();
+        let digits = self.consume_digits(radix);
+        return self.integer_token(&digits, radix, start);
+      }
     }
-    let c = self.current();
-    self.current_position += 1;    
-    return c;
+
+    let mut literal = self.consume_digits(10);
+    let mut is_float = false;
+
+    // A fractional part needs a digit after the dot so that "1..2" and "1.foo"
+    // keep the dot for later tokens.
+    if self.current() == Some('.') && self.peek(1).is_some_and(|c| c.is_ascii_digit()) {
+      self.consume();
+      literal.push('.');
+      literal.push_str(&self.consume_digits(10));
+      is_float = true;
+    }
+
+    if matches!(self.current(), Some('e') | Some('E')) {
+      let exponent_digit_offset = match self.peek(1) {
+        Some('+') | Some('-') => 2,
+        _ => 1
+      };
+      if self.peek(exponent_digit_offset).is_some_and(|c| c.is_ascii_digit()) {
+        literal.push(self.consume().unwrap());
+        if exponent_digit_offset == 2 {
+          literal.push(self.consume().unwrap());
+        }
+        literal.push_str(&self.consume_digits(10));
+        is_float = true;
+      }
+    }
+
+    if is_float {
+      let float = literal.parse::<f64>().unwrap();
+      if float.is_infinite() {
+        let span = self.span(start, self.current_position);
+        self.diagnostics_glossary.borrow_mut().report_float_literal_out_of_range(span);
+        return TokenType::BadNumber;
+      }
+      return TokenType::Float(float);
+    }
+    return self.integer_token(&literal, 10, start);
   }
 
-  fn consume_number(&mut self) -> i64 {
-    let mut number: i64 = 0;
+  fn consume_digits(&mut self, radix: u32) -> String {
+    let mut digits = String::new();
     while let Some(c) = self.current() {
-      if c.is_digit(10) {
-        self.consume().unwrap();
-        number = number * 10 + c.to_digit(10).unwrap() as i64;
+      if c.is_digit(radix) {
+        digits.push(c);
       }
-      else { 
+      else if c != '_' {
         break;
       }
+      self.consume();
     }
-    return number;
+    return digits;
+  }
+
+  fn integer_token(&mut self, digits: &str, radix: u32, start: usize) -> TokenType {
+    let is_big = self.current() == Some('n') && !self.peek(1).is_some_and(|c| identifier::is_identifier_continue(&c));
+    if is_big {
+      self.consume();
+      return TokenType::BigInteger(BigInt::parse_bytes(digits.as_bytes(), radix).unwrap());
+    }
+    return match i64::from_str_radix(digits, radix) {
+      Ok(integer) => TokenType::Number(integer),
+      Err(_) => {
+        let span = self.span(start, self.current_position);
+        self.diagnostics_glossary.borrow_mut().report_integer_literal_out_of_range(span);
+        TokenType::BadNumber
+      }
+    };
   }
 }
\ No newline at end of file
@@ -6,7 +6,12 @@ use self::{
 
 pub mod lexer;
 pub mod parser;
+pub mod operators;
+pub mod incremental;
 pub mod evaluator;
+pub mod identifier;
+pub mod value;
+pub mod builtins;
 pub mod visitor;
 pub mod statement;
 pub mod expression;
@@ -15,6 +20,7 @@ pub mod printer;
 pub mod test;
 
 // Abstract Syntax Tree Module
+#[derive(Default)]
 pub struct SyntaxTree {
   pub statements: Vec<SyntaxTreeStatement>
 }
@@ -34,9 +40,19 @@ impl SyntaxTree {
     }
   }
   
-  pub fn visualise(&self) -> () {
+  pub fn visualise(&self) {
     let mut printer = SyntaxTreePrinter::new();
     self.visit(&mut printer);
     println!("{}", printer.result)
   }
+}
+
+// The syntax tree is parsed, walked by visitors and evaluated recursively.
+// Each of them comes through here once per nested expression, and the stack is
+// extended with a segment on the heap whenever little of it is left, so any
+// thread can do all of it without a stack sized up front.
+pub fn ensure_stack<T>(run: impl FnOnce() -> T) -> T {
+  const RED_ZONE: usize = 256 * 1024;
+  const SEGMENT: usize = 8 * 1024 * 1024;
+  return stacker::maybe_grow(RED_ZONE, SEGMENT, run);
 }
\ No newline at end of file
@@ -0,0 +1,115 @@
+// Operator table driving the Pratt parser. Every operator token of the lexer has
+// a row with its fixity and precedence, a higher precedence binds tighter.
+
+use super::{
+  lexer::TokenType,
+  types::{
+    _binary::BinaryOperatorKind,
+    _unary::UnaryOperatorKind
+  }
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Associativity {
+  Left,
+  Right
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fixity {
+  Prefix(UnaryOperatorKind),
+  Infix(BinaryOperatorKind),
+  // The binary operator of a compound assignment, none for `=`
+  Assignment(Option<BinaryOperatorKind>),
+  Postfix(PostfixOperatorKind)
+}
+
+// Postfix operators open a bracket the parser reads the rest of
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PostfixOperatorKind {
+  Call,
+  Index,
+  Field
+}
+
+pub struct Operator {
+  pub token: TokenType,
+  pub fixity: Fixity,
+  pub precedence: u8,
+  pub associativity: Associativity
+}
+
+const fn prefix(token: TokenType, kind: UnaryOperatorKind, precedence: u8) -> Operator {
+  return Operator { token, fixity: Fixity::Prefix(kind), precedence, associativity: Associativity::Right };
+}
+
+const fn infix(token: TokenType, kind: BinaryOperatorKind, precedence: u8, associativity: Associativity) -> Operator {
+  return Operator { token, fixity: Fixity::Infix(kind), precedence, associativity };
+}
+
+const fn assignment(token: TokenType, kind: Option<BinaryOperatorKind>) -> Operator {
+  return Operator { token, fixity: Fixity::Assignment(kind), precedence: 0, associativity: Associativity::Right };
+}
+
+const fn postfix(token: TokenType, kind: PostfixOperatorKind, precedence: u8) -> Operator {
+  return Operator { token, fixity: Fixity::Postfix(kind), precedence, associativity: Associativity::Left };
+}
+
+// Prefix operators bind tighter than every infix operator except `**`, so
+// `-2 ** 2` is `-(2 ** 2)` like in mathematics.
+// `step` shares the precedence of the ranges, so `0..10 step 2` is the range
+// `0..10` stepped by 2. Assignments bind loosest of all and to the right, so
+// `a = b = 1 + 2` assigns `1 + 2` to `b` and then to `a`. Postfix operators
+// bind tightest, `-f(x) ** 2` is `-((f(x)) ** 2)`.
+static OPERATORS: [Operator; 33] = [
+  assignment(TokenType::Equal, None),
+  assignment(TokenType::PlusEqual, Some(BinaryOperatorKind::Plus)),
+  assignment(TokenType::MinusEqual, Some(BinaryOperatorKind::Minus)),
+  assignment(TokenType::AsteriskEqual, Some(BinaryOperatorKind::Multiply)),
+  assignment(TokenType::ForwardSlashEqual, Some(BinaryOperatorKind::Divide)),
+  infix(TokenType::DoubleDot, BinaryOperatorKind::Range, 1, Associativity::Left),
+  infix(TokenType::DoubleDotEqual, BinaryOperatorKind::RangeInclusive, 1, Associativity::Left),
+  infix(TokenType::Step, BinaryOperatorKind::Step, 1, Associativity::Left),
+  infix(TokenType::DoublePipe, BinaryOperatorKind::LogicalOr, 2, Associativity::Left),
+  infix(TokenType::DoubleAmpersand, BinaryOperatorKind::LogicalAnd, 3, Associativity::Left),
+  infix(TokenType::DoubleEqual, BinaryOperatorKind::Equal, 4, Associativity::Left),
+  infix(TokenType::BangEqual, BinaryOperatorKind::NotEqual, 4, Associativity::Left),
+  infix(TokenType::LessThan, BinaryOperatorKind::LessThan, 4, Associativity::Left),
+  infix(TokenType::LessThanEqual, BinaryOperatorKind::LessThanOrEqual, 4, Associativity::Left),
+  infix(TokenType::GreaterThan, BinaryOperatorKind::GreaterThan, 4, Associativity::Left),
+  infix(TokenType::GreaterThanEqual, BinaryOperatorKind::GreaterThanOrEqual, 4, Associativity::Left),
+  infix(TokenType::Pipe, BinaryOperatorKind::BitwiseOr, 5, Associativity::Left),
+  infix(TokenType::Caret, BinaryOperatorKind::BitwiseXor, 6, Associativity::Left),
+  infix(TokenType::Ampersand, BinaryOperatorKind::BitwiseAnd, 7, Associativity::Left),
+  infix(TokenType::DoubleLessThan, BinaryOperatorKind::ShiftLeft, 8, Associativity::Left),
+  infix(TokenType::DoubleGreaterThan, BinaryOperatorKind::ShiftRight, 8, Associativity::Left),
+  infix(TokenType::Plus, BinaryOperatorKind::Plus, 9, Associativity::Left),
+  infix(TokenType::Minus, BinaryOperatorKind::Minus, 9, Associativity::Left),
+  infix(TokenType::Asterisk, BinaryOperatorKind::Multiply, 10, Associativity::Left),
+  infix(TokenType::ForwardSlash, BinaryOperatorKind::Divide, 10, Associativity::Left),
+  infix(TokenType::Percent, BinaryOperatorKind::Modulo, 10, Associativity::Left),
+  prefix(TokenType::Minus, UnaryOperatorKind::Minus, 11),
+  prefix(TokenType::Tilde, UnaryOperatorKind::BitwiseNot, 11),
+  prefix(TokenType::Bang, UnaryOperatorKind::LogicalNot, 11),
+  infix(TokenType::DoubleAsterisk, BinaryOperatorKind::Power, 12, Associativity::Right),
+  postfix(TokenType::LeftParenthesis, PostfixOperatorKind::Call, 13),
+  postfix(TokenType::LeftBracket, PostfixOperatorKind::Index, 13),
+  postfix(TokenType::Dot, PostfixOperatorKind::Field, 13)
+];
+
+pub fn prefix_operator(token: &TokenType) -> Option<&'static Operator> {
+  return OPERATORS.iter().find(|operator| operator.token == *token && matches!(operator.fixity, Fixity::Prefix(_)));
+}
+
+// Infix, assignment and postfix operators, which all follow an operand
+pub fn trailing_operator(token: &TokenType) -> Option<&'static Operator> {
+  return OPERATORS.iter().find(|operator| operator.token == *token && !matches!(operator.fixity, Fixity::Prefix(_)));
+}
+
+pub fn binary_operator(kind: &BinaryOperatorKind) -> &'static Operator {
+  return OPERATORS.iter().find(|operator| matches!(operator.fixity, Fixity::Infix(infix) if infix == *kind)).unwrap();
+}
+
+pub fn unary_operator(kind: &UnaryOperatorKind) -> &'static Operator {
+  return OPERATORS.iter().find(|operator| matches!(operator.fixity, Fixity::Prefix(prefix) if prefix == *kind)).unwrap();
+}
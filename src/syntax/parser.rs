@@ -1,23 +1,26 @@
-use std::cell::Cell;
+use std::{cell::Cell, rc::Rc};
 
-use crate::diagnostics::DiagnosticGlossaryCell;
+use crate::diagnostics::{DiagnosticGlossary, DiagnosticGlossaryCell};
 
 use super::{
-  expression::SyntaxTreeExpression, 
-  lexer::{Token, TokenType}, 
+  ensure_stack,
+  identifier,
+  expression::{SyntaxTreeExpression, SyntaxTreeExpressionKind}, 
+  lexer::{StringSegment, TextSpan, Token, TokenType}, 
   statement::SyntaxTreeStatement,
   types::{
-    _binary::{
-      BinaryOperator,
-      BinaryOperatorKind
-    }, 
-    _unary::{
-      UnaryOperator,
-      UnaryOperatorKind
-    }
-  }
+    _assignment::AssignmentOperator,
+    _binary::BinaryOperator, 
+    _map::MapEntry,
+    _struct::FieldInitialiser,
+    _number::Number,
+    _string::InterpolationPart,
+    _unary::UnaryOperator
+  },
+  operators::{self, Associativity, Fixity, PostfixOperatorKind}
 };
 
+#[derive(Default)]
 pub struct Counter {
   value: Cell<usize>
 }
@@ -35,12 +38,26 @@ impl Counter {
   pub fn get_value(&self) -> usize { 
     return self.value.get();
   }
+
+  pub fn set_value(&self, value: usize) {
+    self.value.set(value);
+  }
 }
 
 pub struct Parser {
   tokens: Vec<Token>,
   current: Counter,
-  diagnostics_glossary: DiagnosticGlossaryCell
+  diagnostics_glossary: DiagnosticGlossaryCell,
+  // Set after reporting a syntax error until the parser is back on track, the
+  // errors in between are only consequences of the first one
+  recovering: Cell<bool>,
+  // Number of blocks the current statement is nested in
+  block_depth: Cell<usize>,
+  // Cleared while parsing a condition, where the brace after a name opens
+  // the block of the statement and not the fields of a struct literal
+  struct_literals: Cell<bool>,
+  // Number of expressions the current one is nested in
+  depth: Cell<usize>
 }
 
 impl Parser {
@@ -49,19 +66,88 @@ impl Parser {
     diagnostics_glossary: DiagnosticGlossaryCell
   ) -> Self {
     Self {
-      tokens: tokens.iter().filter(
-        |token| token.kind != TokenType::WhiteSpace
-      ).map(|token| token.clone()).collect(),
+      tokens: Token::attach_trivia(tokens),
       current: Counter::new(),
-      diagnostics_glossary
+      diagnostics_glossary,
+      recovering: Cell::new(false),
+      block_depth: Cell::new(0),
+      struct_literals: Cell::new(true),
+      depth: Cell::new(0)
     }
   }
 
+  // Expressions are parsed recursively, growing the stack as needed. Deeper
+  // nesting is rejected still, so that what can't grow the stack, dropping or
+  // cloning a syntax tree, doesn't overflow it.
+  pub const MAX_DEPTH: usize = 1000;
+
   pub fn next_statement(&mut self) -> Option<SyntaxTreeStatement> {
+    self.skip_terminators();
     if self.is_at_end() {
       return None;
     }
-    return Some(self.parse_statement());
+    self.recovering.set(false);
+    let statement = self.parse_statement();
+    self.consume_terminator();
+    return Some(statement);
+  }
+
+  pub fn tokens(&self) -> &[Token] {
+    return &self.tokens;
+  }
+
+  // Index of the current token
+  pub fn position(&self) -> usize {
+    return self.current.get_value();
+  }
+
+  // Continues parsing at the first token starting at or after the byte offset
+  pub fn seek(&self, offset: usize) {
+    let index = self.tokens.iter().position(|token| token.span.start >= offset).unwrap_or(self.tokens.len() - 1);
+    self.current.set_value(index);
+  }
+
+  fn is_terminator(&self) -> bool {
+    return matches!(self.current().kind, TokenType::NewLine | TokenType::SemiColon);
+  }
+
+  pub fn skip_terminators(&self) {
+    while self.is_terminator() {
+      self.consume();
+    }
+  }
+
+  // A statement ends at a line break, a semicolon or the end of input. The rest
+  // of a line with juxtaposed expressions is skipped instead of parsed as
+  // further statements that would only report the same mistake again. A
+  // statement keyword is where the next statement starts in any case, and the
+  // closing brace of the enclosing block ends its last statement. Brackets
+  // opened in the skipped tokens are skipped up to where they close, line
+  // breaks and all, their contents would be just as out of place.
+  fn consume_terminator(&self) {
+    if self.is_block_end() {
+      return;
+    }
+    if !self.is_terminator() && !self.is_at_end() {
+      if !self.follows_bad_token(0) {
+        self.report(|glossary, token| glossary.report_expected_end_of_statement(token));
+      }
+      let mut depth = 0usize;
+      while !self.is_at_end() && (depth > 0 || !(self.is_terminator() || self.current().starts_statement() || self.is_block_end())) {
+        match self.consume().kind {
+          TokenType::LeftBrace | TokenType::LeftParenthesis | TokenType::LeftBracket => depth += 1,
+          TokenType::RightBrace | TokenType::RightParenthesis | TokenType::RightBracket => depth = depth.saturating_sub(1),
+          _ => {}
+        }
+      }
+    }
+    if self.is_terminator() {
+      self.consume();
+    }
+  }
+
+  fn is_block_end(&self) -> bool {
+    return self.block_depth.get() > 0 && self.current().kind == TokenType::RightBrace;
   }
 
   pub fn is_at_end(&self) -> bool {
@@ -72,7 +158,28 @@ impl Parser {
     match self.current().kind {
        TokenType::Let => {
         self.parse_let_statement()
-       }       
+       }
+       TokenType::Fn => {
+        self.parse_function_declaration()
+       }
+       TokenType::Return => {
+        self.parse_return_statement()
+       }
+       TokenType::Struct => {
+        self.parse_struct_declaration()
+       }
+       TokenType::While => {
+        self.parse_while_statement()
+       }
+       TokenType::For => {
+        self.parse_for_statement()
+       }
+       TokenType::Break => {
+        SyntaxTreeStatement::break_statement(self.consume().clone())
+       }
+       TokenType::Continue => {
+        SyntaxTreeStatement::continue_statement(self.consume().clone())
+       }
        _ => {
         self.parse_expression_statement()
        }
@@ -80,111 +187,413 @@ impl Parser {
   }
 
   fn parse_let_statement(&mut self) -> SyntaxTreeStatement {
-    self.consume_and_check(TokenType::Let);
-    let identifier = self.consume_and_check(TokenType::Identifier).clone();
-    self.consume_and_check(TokenType::Equal);
+    let start = self.position();
+    self.expect(TokenType::Let);
+    let mutable = self.current().kind == TokenType::Mut;
+    if mutable {
+      self.consume();
+    }
+    let identifier = Self::identifier(&self.expect(TokenType::Identifier));
+    self.expect(TokenType::Equal);
     let expr = self.parse_expression();
-    return SyntaxTreeStatement::let_statement(identifier, expr);    
+    return SyntaxTreeStatement::let_statement(identifier, mutable, expr, self.span_from(start));    
+  }
+
+  fn parse_function_declaration(&mut self) -> SyntaxTreeStatement {
+    let start = self.position();
+    self.expect(TokenType::Fn);
+    let identifier = Self::identifier(&self.expect(TokenType::Identifier));
+    self.expect(TokenType::LeftParenthesis);
+    let parameters = self.parse_list(TokenType::RightParenthesis, |parser| Self::identifier(&parser.expect(TokenType::Identifier)));
+    let body = self.parse_block_expression();
+    return SyntaxTreeStatement::function_declaration(identifier, parameters, body, self.span_from(start));
+  }
+
+  fn parse_struct_declaration(&mut self) -> SyntaxTreeStatement {
+    let start = self.position();
+    self.expect(TokenType::Struct);
+    let identifier = Self::identifier(&self.expect(TokenType::Identifier));
+    let fields = self.parse_braced_list(|parser| Self::identifier(&parser.expect(TokenType::Identifier)));
+    return SyntaxTreeStatement::struct_declaration(identifier, fields, self.span_from(start));
+  }
+
+  // The value is optional, the end of the statement or block comes right after `return` without one
+  fn parse_return_statement(&mut self) -> SyntaxTreeStatement {
+    let start = self.position();
+    let keyword = self.expect(TokenType::Return);
+    let value = if self.is_synchronising() { None } else { Some(self.parse_expression()) };
+    return SyntaxTreeStatement::return_statement(keyword, value, self.span_from(start));
+  }
+
+  fn parse_while_statement(&mut self) -> SyntaxTreeStatement {
+    let start = self.position();
+    self.expect(TokenType::While);
+    let condition = self.parse_condition();
+    let body = self.parse_block_expression();
+    return SyntaxTreeStatement::while_statement(condition, body, self.span_from(start));
+  }
+
+  fn parse_for_statement(&mut self) -> SyntaxTreeStatement {
+    let start = self.position();
+    self.expect(TokenType::For);
+    let identifier = Self::identifier(&self.expect(TokenType::Identifier));
+    self.expect(TokenType::In);
+    let iterable = self.parse_condition();
+    let body = self.parse_block_expression();
+    return SyntaxTreeStatement::for_statement(identifier, iterable, body, self.span_from(start));
   }
 
   fn parse_expression_statement(&mut self) -> SyntaxTreeStatement {
+    let start = self.position();
     let expr = self.parse_expression();
-    return SyntaxTreeStatement::expression(expr);
+    return SyntaxTreeStatement::expression(expr, self.span_from(start));
+  }
+
+  // `if a == b { ... }` compares with `b`, not with a struct literal of type `b`.
+  // Inside brackets struct literals are fine again, `if a == (Point { x: 1 }) {`.
+  fn parse_condition(&mut self) -> SyntaxTreeExpression {
+    return self.with_struct_literals(false, Self::parse_expression);
+  }
+
+  fn with_struct_literals<T>(&mut self, allowed: bool, parse: impl FnOnce(&mut Self) -> T) -> T {
+    let enclosing = self.struct_literals.replace(allowed);
+    let result = parse(self);
+    self.struct_literals.set(enclosing);
+    return result;
   }
 
   fn parse_expression(&mut self) -> SyntaxTreeExpression {
     return self.parse_binary_expression(0);
   }
 
+  // Pratt parser, precedence and associativity of every operator come from
+  // the operator table, through BinaryOperator for the binary ones. Operators
+  // binding looser than `precedence` are left to the caller.
+  // Every nested expression is parsed through here, an operand of a unary
+  // operator too, so this is where the nesting is bounded. Past the limit the
+  // rest of the input is skipped, there is no telling where it would end.
   fn parse_binary_expression(&mut self, precedence: u8) -> SyntaxTreeExpression {
-    let mut left = self.parse_primary_expression();    
-    while let Some(operator) = self.parse_binary_operator() {
-      let operator_precedence = operator.precedence();      
-      if operator_precedence < precedence {
-        break;
+    if self.depth.get() == Self::MAX_DEPTH {
+      self.diagnostics_glossary.borrow_mut().report_nesting_too_deep(self.current(), Self::MAX_DEPTH);
+      self.recovering.set(true);
+      let start = self.position();
+      while !self.is_at_end() {
+        self.consume();
       }
-      // Consume token later to ensure the current token that's being parsed doesn't move up incase of a lower precedence binary operator being parsed
-      // in the right. Example of error caused with statement like "let a = (1 + 2) * b + 3"
-      self.consume();
-      let right = self.parse_binary_expression(operator_precedence);
-      left = SyntaxTreeExpression::binary(operator, left, right);
+      return SyntaxTreeExpression::error(self.span_from(start));
+    }
+    self.depth.set(self.depth.get() + 1);
+    let expression = ensure_stack(|| self.parse_nested_binary_expression(precedence));
+    self.depth.set(self.depth.get() - 1);
+    return expression;
+  }
+
+  fn parse_nested_binary_expression(&mut self, precedence: u8) -> SyntaxTreeExpression {
+    let start = self.position();
+    let mut left = self.parse_unary_expression();
+    while let Some(operator) = operators::trailing_operator(&self.current().kind) {
+      left = match operator.fixity {
+        Fixity::Infix(kind) => {
+          let operator = BinaryOperator::new(kind, self.current().clone());
+          if operator.precedence() < precedence {
+            break;
+          }
+          self.consume();
+          let right = self.parse_binary_expression(Self::right_precedence(operator.precedence(), operator.associativity()));
+          SyntaxTreeExpression::binary(operator, left, right, self.span_from(start))
+        }
+        _ if operator.precedence < precedence => break,
+        Fixity::Postfix(kind) => self.parse_postfix_expression(kind, left, start),
+        Fixity::Assignment(kind) => {
+          let token = self.consume().clone();
+          let right = self.parse_binary_expression(Self::right_precedence(operator.precedence, operator.associativity));
+          self.parse_assignment(AssignmentOperator::new(kind, token), left, right, start)
+        }
+        Fixity::Prefix(_) => unreachable!("Prefix operators don't follow an operand")
+      };
     }
     return left;
   }
 
-  fn parse_binary_operator(&mut self) -> Option<BinaryOperator> {
-    let token = self.current();
-    let kind = match token.kind {
-      TokenType::Plus => {
-        Some(BinaryOperatorKind::Plus)
+  // A right associative operator takes operators of its own precedence on
+  // its right, so `2 ** 3 ** 2` is `2 ** (3 ** 2)`
+  fn right_precedence(precedence: u8, associativity: Associativity) -> u8 {
+    return match associativity {
+      Associativity::Left => precedence + 1,
+      Associativity::Right => precedence
+    };
+  }
+
+  fn parse_postfix_expression(&mut self, kind: PostfixOperatorKind, operand: SyntaxTreeExpression, start: usize) -> SyntaxTreeExpression {
+    return match kind {
+      PostfixOperatorKind::Call => {
+        self.expect(TokenType::LeftParenthesis);
+        let arguments = self.parse_list(TokenType::RightParenthesis, Self::parse_expression);
+        SyntaxTreeExpression::call(operand, arguments, self.span_from(start))
       }
-      TokenType::Minus => {
-        Some(BinaryOperatorKind::Minus)
+      PostfixOperatorKind::Index => {
+        self.expect(TokenType::LeftBracket);
+        let index = self.with_struct_literals(true, Self::parse_expression);
+        self.expect(TokenType::RightBracket);
+        SyntaxTreeExpression::index(operand, index, self.span_from(start))
       }
-      TokenType::Asterisk => {
-        Some(BinaryOperatorKind::Multiply)
+      PostfixOperatorKind::Field => {
+        self.expect(TokenType::Dot);
+        let field = Self::identifier(&self.expect(TokenType::Identifier));
+        SyntaxTreeExpression::field(operand, field, self.span_from(start))
       }
-      TokenType::ForwardSlash => {
-        Some(BinaryOperatorKind::Divide)
+    };
+  }
+
+  // Comma separated items up to the closing token, a trailing comma is allowed.
+  // An item that doesn't start where one should ends the list early.
+  fn parse_list<T>(&mut self, close: TokenType, mut parse_item: impl FnMut(&mut Self) -> T) -> Vec<T> {
+    let enclosing = self.struct_literals.replace(true);
+    let mut items = Vec::new();
+    while self.current().kind != close && !self.is_synchronising() {
+      items.push(parse_item(self));
+      if self.current().kind != TokenType::Comma {
+        break;
       }
-      _ => {
-        None
+      self.consume();
+    }
+    self.struct_literals.set(enclosing);
+    self.expect(close);
+    return items;
+  }
+
+  // Comma separated items between braces, like `parse_list`. Line breaks
+  // inside braces end statements, but not between the items here.
+  fn parse_braced_list<T>(&mut self, mut parse_item: impl FnMut(&mut Self) -> T) -> Vec<T> {
+    let enclosing = self.struct_literals.replace(true);
+    self.expect(TokenType::LeftBrace);
+    let mut items = Vec::new();
+    loop {
+      self.skip_terminators();
+      if self.is_at_end() || self.current().kind == TokenType::RightBrace || self.current().starts_statement() {
+        break;
       }
+      items.push(parse_item(self));
+      self.skip_terminators();
+      if self.current().kind != TokenType::Comma {
+        break;
+      }
+      self.consume();
+    }
+    self.struct_literals.set(enclosing);
+    self.expect(TokenType::RightBrace);
+    return items;
+  }
+
+  fn parse_assignment(&self, operator: AssignmentOperator, target: SyntaxTreeExpression, value: SyntaxTreeExpression, start: usize) -> SyntaxTreeExpression {
+    if !Self::is_assignable(&target) {
+      self.diagnostics_glossary.borrow_mut().report_invalid_assignment_target(target.span.clone());
+    }
+    return SyntaxTreeExpression::assignment(target, operator, value, self.span_from(start));
+  }
+
+  // A variable, or an element or a field of a value held by a variable
+  fn is_assignable(target: &SyntaxTreeExpression) -> bool {
+    return match &target.kind {
+      SyntaxTreeExpressionKind::Variable(_) | SyntaxTreeExpressionKind::Error(_) | SyntaxTreeExpressionKind::Missing(_) => true,
+      SyntaxTreeExpressionKind::Index(index) => Self::is_assignable(&index.target),
+      SyntaxTreeExpressionKind::Field(field) => Self::is_assignable(&field.target),
+      _ => false
     };
-    return kind.map(|kind| BinaryOperator::new(kind, token.clone()));
   }
 
   fn parse_unary_operator(&mut self) -> Option<UnaryOperator> {
     let token = self.current();
-    let kind = match token.kind {
-      TokenType::Minus => {
-        Some(UnaryOperatorKind::Minus)
-      },
-      TokenType::Tilde => {
-        Some(UnaryOperatorKind::BitwiseNot)
-      },
-      _ => {
-        None
-      }      
-    };
-    return kind.map(|kind| UnaryOperator::new(kind, token.clone()));
+    return operators::prefix_operator(&token.kind).and_then(|operator| match operator.fixity {
+      Fixity::Prefix(kind) => Some(UnaryOperator::new(kind, token.clone())),
+      Fixity::Infix(_) | Fixity::Assignment(_) | Fixity::Postfix(_) => None
+    });
   }
 
   fn parse_unary_expression(&mut self) -> SyntaxTreeExpression {
     if let Some(operator) = self.parse_unary_operator() {
+      let start = self.position();
       self.consume();
-      let operand = self.parse_unary_expression();
-      return SyntaxTreeExpression::unary(operator, operand);
+      let operand = self.parse_binary_expression(operator.precedence());
+      return SyntaxTreeExpression::unary(operator, operand, self.span_from(start));
     }
     return self.parse_primary_expression();
   }
 
   fn parse_primary_expression(&mut self) -> SyntaxTreeExpression {
+    // Tokens the statement or an enclosing expression can go on with are left
+    // alone, the expression is just missing
+    if self.is_synchronising() {
+      if !self.follows_bad_token(0) {
+        self.report(|glossary, token| glossary.report_expexted_expression(token));
+      }
+      return SyntaxTreeExpression::missing(self.missing_span());
+    }
+    match self.current().kind {
+      TokenType::If => return self.parse_if_expression(),
+      TokenType::LeftBrace if self.starts_map() => return self.parse_map_expression(),
+      TokenType::LeftBrace => return self.parse_block_expression(),
+      TokenType::Pipe | TokenType::DoublePipe => return self.parse_closure_expression(),
+      TokenType::LeftBracket => return self.parse_array_expression(),
+      // The lexer reported it already
+      TokenType::BadNumber => return SyntaxTreeExpression::error(self.consume().span.clone()),
+      _ => {}
+    }
+    if !matches!(self.current().kind, TokenType::Number(_) | TokenType::Float(_) | TokenType::BigInteger(_) | TokenType::String(_) | TokenType::True | TokenType::False | TokenType::LeftParenthesis | TokenType::Identifier) {
+      if !self.follows_bad_token(0) {
+        self.report(|glossary, token| glossary.report_expexted_expression(token));
+      }
+      return SyntaxTreeExpression::error(self.consume().span.clone());
+    }
+    self.recovering.set(false);
+    let start = self.position();
     let token = self.consume();
-    return match token.kind {
+    return match &token.kind {
       TokenType::Number(number) => {
-        SyntaxTreeExpression::number(number)
+        SyntaxTreeExpression::number(Number::Integer(*number), token.span.clone())
       }
-      TokenType::LeftParenthesis => {
-        let expr = self.parse_expression();
-        let token = self.consume();
-        if token.kind != TokenType::RightParenthesis {
-          panic!("Expected Right Parenthesis")
-        }
-        SyntaxTreeExpression::parenthsised(expr)        
+      TokenType::Float(number) => {
+        SyntaxTreeExpression::number(Number::Float(*number), token.span.clone())
       }
-      TokenType::Identifier => {
-        SyntaxTreeExpression::identifier(token.clone())
+      TokenType::BigInteger(number) => {
+        SyntaxTreeExpression::number(Number::BigInteger(number.clone()), token.span.clone())
+      }
+      TokenType::True | TokenType::False => {
+        SyntaxTreeExpression::boolean(token.kind == TokenType::True, token.span.clone())
+      }
+      TokenType::String(segments) => {
+        let span = token.span.clone();
+        self.parse_string(segments.clone(), span)
+      }
+      TokenType::LeftParenthesis => {
+        let expr = self.with_struct_literals(true, Self::parse_expression);
+        self.expect(TokenType::RightParenthesis);
+        SyntaxTreeExpression::parenthsised(expr, self.span_from(start))
       }
       _ => {
-        self.diagnostics_glossary.borrow_mut().report_expexted_expression(token);
-        SyntaxTreeExpression::error(
-          token.span.clone()
-        )
+        let identifier = Self::identifier(token);
+        if self.current().kind == TokenType::LeftBrace && self.struct_literals.get() {
+          return self.parse_struct_literal(identifier, start);
+        }
+        SyntaxTreeExpression::identifier(identifier)
       }
     }
   }
 
+  fn parse_if_expression(&mut self) -> SyntaxTreeExpression {
+    let start = self.position();
+    self.expect(TokenType::If);
+    let condition = self.parse_condition();
+    let then_branch = self.parse_block_expression();
+    let else_branch = if self.current().kind == TokenType::Else {
+      self.consume();
+      Some(if self.current().kind == TokenType::If { self.parse_if_expression() } else { self.parse_block_expression() })
+    } else {
+      None
+    };
+    return SyntaxTreeExpression::if_expression(condition, then_branch, else_branch, self.span_from(start));
+  }
+
+  // `||` is a closure without parameters. The body takes everything up to
+  // the end of the expression, so `|x| x + 1` is a closure returning `x + 1`.
+  fn parse_closure_expression(&mut self) -> SyntaxTreeExpression {
+    let start = self.position();
+    let parameters = match self.consume().kind {
+      TokenType::DoublePipe => Vec::new(),
+      _ => self.parse_list(TokenType::Pipe, |parser| Self::identifier(&parser.expect(TokenType::Identifier)))
+    };
+    let body = self.parse_expression();
+    return SyntaxTreeExpression::closure(parameters, body, self.span_from(start));
+  }
+
+  fn parse_array_expression(&mut self) -> SyntaxTreeExpression {
+    let start = self.position();
+    self.expect(TokenType::LeftBracket);
+    let elements = self.parse_list(TokenType::RightBracket, Self::parse_expression);
+    return SyntaxTreeExpression::array(elements, self.span_from(start));
+  }
+
+  // A brace opens a map rather than a block when a single token key and a
+  // colon follow it, or just a colon for the empty map `{:}`. Any other key is
+  // put in parentheses, `{ (a + b): 1 }`.
+  fn starts_map(&self) -> bool {
+    return self.peek(1).kind == TokenType::Colon || self.peek(2).kind == TokenType::Colon;
+  }
+
+  fn parse_map_expression(&mut self) -> SyntaxTreeExpression {
+    let start = self.position();
+    if self.peek(1).kind == TokenType::Colon {
+      self.expect(TokenType::LeftBrace);
+      self.expect(TokenType::Colon);
+      self.expect(TokenType::RightBrace);
+      return SyntaxTreeExpression::map(Vec::new(), self.span_from(start));
+    }
+    let entries = self.parse_braced_list(|parser| {
+      let key = parser.parse_expression();
+      parser.expect(TokenType::Colon);
+      let value = parser.parse_expression();
+      return MapEntry { key, value };
+    });
+    return SyntaxTreeExpression::map(entries, self.span_from(start));
+  }
+
+  fn parse_struct_literal(&mut self, identifier: Token, start: usize) -> SyntaxTreeExpression {
+    let initialisers = self.parse_braced_list(|parser| {
+      let field = Self::identifier(&parser.expect(TokenType::Identifier));
+      parser.expect(TokenType::Colon);
+      let value = parser.parse_expression();
+      return FieldInitialiser { field, value };
+    });
+    return SyntaxTreeExpression::struct_literal(identifier, initialisers, self.span_from(start));
+  }
+
+  fn parse_block_expression(&mut self) -> SyntaxTreeExpression {
+    let start = self.position();
+    // Without an opening brace, the statements that follow are not taken to be the block's
+    if self.expect(TokenType::LeftBrace).is_missing() {
+      return SyntaxTreeExpression::block(Vec::new(), self.missing_span());
+    }
+    self.block_depth.set(self.block_depth.get() + 1);
+    let enclosing = self.struct_literals.replace(true);
+    let mut statements = Vec::new();
+    loop {
+      self.skip_terminators();
+      if self.is_at_end() || self.current().kind == TokenType::RightBrace {
+        break;
+      }
+      statements.push(self.parse_statement());
+      self.consume_terminator();
+    }
+    self.struct_literals.set(enclosing);
+    self.block_depth.set(self.block_depth.get() - 1);
+    self.expect(TokenType::RightBrace);
+    return SyntaxTreeExpression::block(statements, self.span_from(start));
+  }
+
+  fn parse_string(&mut self, segments: Vec<StringSegment>, span: TextSpan) -> SyntaxTreeExpression {
+    if let [StringSegment::Text(text)] = segments.as_slice() {
+      return SyntaxTreeExpression::string(text.clone(), span);
+    }
+    let parts = segments.into_iter().map(|segment| match segment {
+      StringSegment::Text(text) => InterpolationPart::Text(text),
+      StringSegment::Interpolation(tokens) => InterpolationPart::Expression(self.parse_interpolation(tokens))
+    }).collect();
+    return SyntaxTreeExpression::interpolation(parts, span);
+  }
+
+  // Each `${...}` carries its own token stream which is parsed by a nested parser
+  // sharing this parser's diagnostics.
+  fn parse_interpolation(&self, tokens: Vec<Token>) -> SyntaxTreeExpression {
+    let mut parser = Parser::new(tokens, Rc::clone(&self.diagnostics_glossary));
+    parser.depth.set(self.depth.get());
+    let expr = parser.parse_expression();
+    parser.skip_terminators();
+    if !parser.is_at_end() {
+      self.diagnostics_glossary.borrow_mut().report_unexpected_interpolation_token(parser.current());
+    }
+    return expr;
+  }
+
   fn peek(&self, offset: isize) -> &Token {
     let mut index = (self.current.get_value() as isize + offset) as usize;
     if index >= self.tokens.len() {
@@ -202,11 +611,75 @@ impl Parser {
     return self.peek(-1);
   }
 
-  fn consume_and_check(&self, kind: TokenType) -> &Token {
-    let token = self.consume();
-    if token.kind != kind {
-      self.diagnostics_glossary.borrow_mut().report_unexpected_token(&kind, token);
+  // Source of the tokens from start up to the current token, without the trivia
+  // before the first and after the last of them.
+  fn span_from(&self, start: usize) -> TextSpan {
+    let tokens = &self.tokens[start..self.position().min(self.tokens.len())];
+    if tokens.is_empty() {
+      return self.missing_span();
+    }
+    let mut literal = String::new();
+    for (index, token) in tokens.iter().enumerate() {
+      if index > 0 {
+        token.leading_trivia.iter().for_each(|trivia| literal.push_str(&trivia.span.literal));
+      }
+      literal.push_str(&token.span.literal);
+      if index + 1 < tokens.len() {
+        token.trailing_trivia.iter().for_each(|trivia| literal.push_str(&trivia.span.literal));
+      }
+    }
+    return TextSpan::new(tokens[0].span.start, tokens[tokens.len() - 1].span.end, literal);
+  }
+
+  // Tokens keep the source spelling so the stream stays lossless, names in the
+  // tree are NFC normalised so that different spellings bind the same variable.
+  fn identifier(token: &Token) -> Token {
+    let mut identifier = token.clone();
+    identifier.span.literal = identifier::normalise(&token.span.literal);
+    return identifier;
+  }
+
+  // Bad characters were reported by the lexer already, an error right after
+  // skipping them would only repeat the same problem.
+  fn follows_bad_token(&self, offset: isize) -> bool {
+    let is_bad = |trivia: &Vec<Token>| trivia.iter().any(|token| token.kind == TokenType::BadChar);
+    let index = self.current.get_value() as isize + offset;
+    return is_bad(&self.peek(offset).leading_trivia) || (index > 0 && is_bad(&self.peek(offset - 1).trailing_trivia));
+  }
+
+  // Consumes the expected token. Any other token is left for the caller to
+  // make sense of and a zero width token stands in for the missing one.
+  fn expect(&self, kind: TokenType) -> Token {
+    if self.current().kind == kind {
+      self.recovering.set(false);
+      return self.consume().clone();
+    }
+    if !self.follows_bad_token(0) {
+      self.report(|glossary, token| glossary.report_unexpected_token(&kind, token));
     }
-    return token;
+    return Token::missing(kind, self.missing_span().start);
   }
+
+  // Reports an error about the current token, unless it would only be a
+  // consequence of the previous error
+  fn report(&self, report: impl FnOnce(&mut DiagnosticGlossary, &Token)) {
+    if !self.recovering.replace(true) {
+      report(&mut self.diagnostics_glossary.borrow_mut(), self.current());
+    }
+  }
+
+  // Where something missing would have been, right after the previous token
+  fn missing_span(&self) -> TextSpan {
+    let position = if self.position() == 0 { self.current().span.start } else { self.peek(-1).span.end };
+    return TextSpan::new(position, position, String::new());
+  }
+
+  // Tokens an expression never starts with but which end a statement or close
+  // an enclosing expression
+  fn is_synchronising(&self) -> bool {
+    let token = self.current();
+    return self.is_terminator() || self.is_at_end() || token.starts_statement() ||
+      matches!(token.kind, TokenType::RightParenthesis | TokenType::RightBracket | TokenType::RightBrace);
+  }
+
 }
\ No newline at end of file
@@ -3,18 +3,38 @@ use termion::color::{self, Fg, Reset};
 use crate::syntax::types::_unary::UnaryExpression;
 
 use super::{  
-  lexer::TextSpan, 
+  lexer::{TextSpan, Token}, 
   statement::SyntaxTreeStatement, 
   types::{
+    _array::ArrayExpression,
+    _assignment::AssignmentExpression,
     _binary::BinaryExpression, 
+    _block::BlockExpression,
+    _boolean::BooleanExpression,
+    _call::CallExpression,
+    _closure::ClosureExpression,
+    _for::ForStatement,
+    _function::FunctionDeclaration,
+    _if::IfExpression,
+    _index::IndexExpression,
+    _map::MapExpression,
     _let::LetStatement, 
     _number::NumberExpression, 
     _parenthesis::ParenthesisExpression, 
-    _variable::VariableExpression
+    _return::ReturnStatement,
+    _struct::{FieldExpression, StructDeclaration, StructLiteralExpression},
+    _string::{
+      InterpolationExpression,
+      InterpolationPart,
+      StringExpression
+    },
+    _variable::VariableExpression,
+    _while::WhileStatement
   },
   visitor::SyntaxTreeVisitor
 };
 
+#[derive(Default)]
 pub struct SyntaxTreePrinter {
   pub indent: usize,
   pub(crate) result: String
@@ -25,23 +45,43 @@ impl SyntaxTreePrinter {
   const TEXT_COLOR: color::LightWhite = color::LightWhite;
   const KEYWORD_COLOR: color::Magenta = color::Magenta;
   const VARIABLLE_COLOR: color::Green = color::Green;
+  const STRING_COLOR: color::Yellow = color::Yellow;
 
   pub fn new() -> Self {
     return Self { indent: 0, result: String::new() };
   }
 
   fn add_whitespace(&mut self) {
-    self.result.push_str(" ");
+    self.result.push(' ');
   }
 
-  fn add_new_line(&mut self) {
+  pub fn add_new_line(&mut self) {
     self.result.push_str("
     ");
   }  
 
-  fn print_with_indent(&mut self, text: &str) {
+  pub fn print_with_indent(&mut self, text: &str) {
     println!("{}{}", " ".repeat(self.indent), text);
   }
+
+  // Inverse of the lexer's escape handling so printed strings lex back to the same value
+  fn escape_string(text: &str) -> String {
+    let mut escaped = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+      match c {
+        '\n' => escaped.push_str("\\n"),
+        '\t' => escaped.push_str("\\t"),
+        '\r' => escaped.push_str("\\r"),
+        '\0' => escaped.push_str("\\0"),
+        '\\' => escaped.push_str("\\\\"),
+        '"' => escaped.push_str("\\\""),
+        '$' if chars.peek() == Some(&'{') => escaped.push_str("\\$"),
+        c => escaped.push(c)
+      }
+    }
+    return escaped;
+  }
 }
 
 impl SyntaxTreeVisitor for SyntaxTreePrinter {
@@ -49,6 +89,10 @@ impl SyntaxTreeVisitor for SyntaxTreePrinter {
   fn visit_let_statement(&mut self, let_statement: &LetStatement) {
       self.result.push_str(&format!("{}let", Self::KEYWORD_COLOR.fg_str()));
       self.add_whitespace();
+      if let_statement.mutable {
+        self.result.push_str(&format!("{}mut", Self::KEYWORD_COLOR.fg_str()));
+        self.add_whitespace();
+      }
       self.result.push_str(&format!("{}{}", Self::TEXT_COLOR.fg_str(), let_statement.identifier.span.literal, ));
       self.add_whitespace();
       self.result.push_str(&format!("{}=", Self::TEXT_COLOR.fg_str(),));
@@ -56,6 +100,78 @@ impl SyntaxTreeVisitor for SyntaxTreePrinter {
       self.visit_expression(&let_statement.initialiser);
   }
 
+  fn visit_function_declaration(&mut self, function_declaration: &FunctionDeclaration) {
+    self.result.push_str(&format!("{}fn", Self::KEYWORD_COLOR.fg_str()));
+    self.add_whitespace();
+    self.result.push_str(&format!("{}{}", Self::VARIABLLE_COLOR.fg_str(), function_declaration.identifier.span.literal));
+    self.result.push_str(&format!("{}(", Self::TEXT_COLOR.fg_str()));
+    for (index, parameter) in function_declaration.parameters.iter().enumerate() {
+      if index > 0 {
+        self.result.push_str(&format!("{},", Self::TEXT_COLOR.fg_str()));
+        self.add_whitespace();
+      }
+      self.result.push_str(&format!("{}{}", Self::VARIABLLE_COLOR.fg_str(), parameter.span.literal));
+    }
+    self.result.push_str(&format!("{})", Self::TEXT_COLOR.fg_str()));
+    self.add_whitespace();
+    self.visit_expression(&function_declaration.body);
+  }
+
+  fn visit_struct_declaration(&mut self, struct_declaration: &StructDeclaration) {
+    self.result.push_str(&format!("{}struct", Self::KEYWORD_COLOR.fg_str()));
+    self.add_whitespace();
+    self.result.push_str(&format!("{}{}", Self::VARIABLLE_COLOR.fg_str(), struct_declaration.identifier.span.literal));
+    self.add_whitespace();
+    self.result.push_str(&format!("{}{{", Self::TEXT_COLOR.fg_str()));
+    for (index, field) in struct_declaration.fields.iter().enumerate() {
+      if index > 0 {
+        self.result.push_str(&format!("{},", Self::TEXT_COLOR.fg_str()));
+      }
+      self.add_whitespace();
+      self.result.push_str(&format!("{}{}", Self::TEXT_COLOR.fg_str(), field.span.literal));
+    }
+    if !struct_declaration.fields.is_empty() {
+      self.add_whitespace();
+    }
+    self.result.push_str(&format!("{}}}", Self::TEXT_COLOR.fg_str()));
+  }
+
+  fn visit_return_statement(&mut self, return_statement: &ReturnStatement) {
+    self.result.push_str(&format!("{}return", Self::KEYWORD_COLOR.fg_str()));
+    if let Some(value) = &return_statement.value {
+      self.add_whitespace();
+      self.visit_expression(value);
+    }
+  }
+
+  fn visit_while_statement(&mut self, while_statement: &WhileStatement) {
+    self.result.push_str(&format!("{}while", Self::KEYWORD_COLOR.fg_str()));
+    self.add_whitespace();
+    self.visit_expression(&while_statement.condition);
+    self.add_whitespace();
+    self.visit_expression(&while_statement.body);
+  }
+
+  fn visit_for_statement(&mut self, for_statement: &ForStatement) {
+    self.result.push_str(&format!("{}for", Self::KEYWORD_COLOR.fg_str()));
+    self.add_whitespace();
+    self.result.push_str(&format!("{}{}", Self::VARIABLLE_COLOR.fg_str(), for_statement.identifier.span.literal));
+    self.add_whitespace();
+    self.result.push_str(&format!("{}in", Self::KEYWORD_COLOR.fg_str()));
+    self.add_whitespace();
+    self.visit_expression(&for_statement.iterable);
+    self.add_whitespace();
+    self.visit_expression(&for_statement.body);
+  }
+
+  fn visit_break_statement(&mut self, _keyword: &Token) {
+    self.result.push_str(&format!("{}break", Self::KEYWORD_COLOR.fg_str()));
+  }
+
+  fn visit_continue_statement(&mut self, _keyword: &Token) {
+    self.result.push_str(&format!("{}continue", Self::KEYWORD_COLOR.fg_str()));
+  }
+
   fn visit_statement(&mut self, statement: &SyntaxTreeStatement) {
     Self::do_visit_statement(self, statement);
     self.result.push_str(&format!("{}\n", Fg(Reset),));
@@ -69,10 +185,35 @@ impl SyntaxTreeVisitor for SyntaxTreePrinter {
     self.result.push_str(&format!("{}{}", Self::TEXT_COLOR.fg_str(), span.literal,));
   }
 
+  fn visit_string(&mut self, string: &StringExpression) {
+    self.result.push_str(&format!("{}\"{}\"", Self::STRING_COLOR.fg_str(), Self::escape_string(&string.value)));
+  }
+
+  fn visit_interpolation_expression(&mut self, interpolation_expression: &InterpolationExpression) {
+    self.result.push_str(&format!("{}\"", Self::STRING_COLOR.fg_str()));
+    for part in &interpolation_expression.parts {
+      match part {
+        InterpolationPart::Text(text) => {
+          self.result.push_str(&format!("{}{}", Self::STRING_COLOR.fg_str(), Self::escape_string(text)));
+        }
+        InterpolationPart::Expression(expr) => {
+          self.result.push_str(&format!("{}${{", Self::STRING_COLOR.fg_str()));
+          self.visit_expression(expr);
+          self.result.push_str(&format!("{}}}", Self::STRING_COLOR.fg_str()));
+        }
+      }
+    }
+    self.result.push_str(&format!("{}\"", Self::STRING_COLOR.fg_str()));
+  }
+
   fn visit_number(&mut self, number: &NumberExpression) {
     self.result.push_str(&format!("{}{}", Self::NUMBER_COLOR.fg_str(), number.number,));
   }
 
+  fn visit_boolean(&mut self, boolean: &BooleanExpression) {
+    self.result.push_str(&format!("{}{}", Self::KEYWORD_COLOR.fg_str(), boolean.value,));
+  }
+
   fn visit_binary_expression(&mut self, binary_expression: &BinaryExpression) {
     self.visit_expression(&binary_expression.left);
     self.add_whitespace();
@@ -81,11 +222,135 @@ impl SyntaxTreeVisitor for SyntaxTreePrinter {
     self.visit_expression(&binary_expression.right);
   }
 
+  fn visit_assignment_expression(&mut self, assignment_expression: &AssignmentExpression) {
+    self.visit_expression(&assignment_expression.target);
+    self.add_whitespace();
+    self.result.push_str(&format!("{}{}", Self::TEXT_COLOR.fg_str(), assignment_expression.operator.token.span.literal));
+    self.add_whitespace();
+    self.visit_expression(&assignment_expression.value);
+  }
+
   fn visit_unary_expression(&mut self, unary_expression: &UnaryExpression) {
     self.result.push_str(&format!("{}{}", Self::TEXT_COLOR.fg_str(), unary_expression.operator.token.span.literal, ));
     self.visit_expression(&unary_expression.operand);
   }  
 
+  fn visit_block_expression(&mut self, block_expression: &BlockExpression) {
+    self.result.push_str(&format!("{}{{\n", Self::TEXT_COLOR.fg_str()));
+    self.indent += 2;
+    for statement in &block_expression.statements {
+      self.result.push_str(&" ".repeat(self.indent));
+      self.visit_statement(statement);
+    }
+    self.indent -= 2;
+    self.result.push_str(&format!("{}{}}}", " ".repeat(self.indent), Self::TEXT_COLOR.fg_str()));
+  }
+
+  fn visit_if_expression(&mut self, if_expression: &IfExpression) {
+    self.result.push_str(&format!("{}if", Self::KEYWORD_COLOR.fg_str()));
+    self.add_whitespace();
+    self.visit_expression(&if_expression.condition);
+    self.add_whitespace();
+    self.visit_expression(&if_expression.then_branch);
+    if let Some(else_branch) = &if_expression.else_branch {
+      self.add_whitespace();
+      self.result.push_str(&format!("{}else", Self::KEYWORD_COLOR.fg_str()));
+      self.add_whitespace();
+      self.visit_expression(else_branch);
+    }
+  }
+
+  fn visit_call_expression(&mut self, call_expression: &CallExpression) {
+    self.visit_expression(&call_expression.callee);
+    self.result.push_str(&format!("{}(", Self::TEXT_COLOR.fg_str()));
+    for (index, argument) in call_expression.arguments.iter().enumerate() {
+      if index > 0 {
+        self.result.push_str(&format!("{},", Self::TEXT_COLOR.fg_str()));
+        self.add_whitespace();
+      }
+      self.visit_expression(argument);
+    }
+    self.result.push_str(&format!("{})", Self::TEXT_COLOR.fg_str()));
+  }
+
+  fn visit_closure_expression(&mut self, closure_expression: &ClosureExpression) {
+    self.result.push_str(&format!("{}|", Self::TEXT_COLOR.fg_str()));
+    for (index, parameter) in closure_expression.parameters.iter().enumerate() {
+      if index > 0 {
+        self.result.push_str(&format!("{},", Self::TEXT_COLOR.fg_str()));
+        self.add_whitespace();
+      }
+      self.result.push_str(&format!("{}{}", Self::VARIABLLE_COLOR.fg_str(), parameter.span.literal));
+    }
+    self.result.push_str(&format!("{}|", Self::TEXT_COLOR.fg_str()));
+    self.add_whitespace();
+    self.visit_expression(&closure_expression.body);
+  }
+
+  fn visit_array_expression(&mut self, array_expression: &ArrayExpression) {
+    self.result.push_str(&format!("{}[", Self::TEXT_COLOR.fg_str()));
+    for (index, element) in array_expression.elements.iter().enumerate() {
+      if index > 0 {
+        self.result.push_str(&format!("{},", Self::TEXT_COLOR.fg_str()));
+        self.add_whitespace();
+      }
+      self.visit_expression(element);
+    }
+    self.result.push_str(&format!("{}]", Self::TEXT_COLOR.fg_str()));
+  }
+
+  fn visit_map_expression(&mut self, map_expression: &MapExpression) {
+    if map_expression.entries.is_empty() {
+      self.result.push_str(&format!("{}{{:}}", Self::TEXT_COLOR.fg_str()));
+      return;
+    }
+    self.result.push_str(&format!("{}{{", Self::TEXT_COLOR.fg_str()));
+    self.add_whitespace();
+    for (index, entry) in map_expression.entries.iter().enumerate() {
+      if index > 0 {
+        self.result.push_str(&format!("{},", Self::TEXT_COLOR.fg_str()));
+        self.add_whitespace();
+      }
+      self.visit_expression(&entry.key);
+      self.result.push_str(&format!("{}:", Self::TEXT_COLOR.fg_str()));
+      self.add_whitespace();
+      self.visit_expression(&entry.value);
+    }
+    self.add_whitespace();
+    self.result.push_str(&format!("{}}}", Self::TEXT_COLOR.fg_str()));
+  }
+
+  fn visit_struct_literal_expression(&mut self, struct_literal_expression: &StructLiteralExpression) {
+    self.result.push_str(&format!("{}{}", Self::VARIABLLE_COLOR.fg_str(), struct_literal_expression.identifier.span.literal));
+    self.add_whitespace();
+    self.result.push_str(&format!("{}{{", Self::TEXT_COLOR.fg_str()));
+    for (index, initialiser) in struct_literal_expression.initialisers.iter().enumerate() {
+      if index > 0 {
+        self.result.push_str(&format!("{},", Self::TEXT_COLOR.fg_str()));
+      }
+      self.add_whitespace();
+      self.result.push_str(&format!("{}{}:", Self::TEXT_COLOR.fg_str(), initialiser.field.span.literal));
+      self.add_whitespace();
+      self.visit_expression(&initialiser.value);
+    }
+    if !struct_literal_expression.initialisers.is_empty() {
+      self.add_whitespace();
+    }
+    self.result.push_str(&format!("{}}}", Self::TEXT_COLOR.fg_str()));
+  }
+
+  fn visit_field_expression(&mut self, field_expression: &FieldExpression) {
+    self.visit_expression(&field_expression.target);
+    self.result.push_str(&format!("{}.{}", Self::TEXT_COLOR.fg_str(), field_expression.field.span.literal));
+  }
+
+  fn visit_index_expression(&mut self, index_expression: &IndexExpression) {
+    self.visit_expression(&index_expression.target);
+    self.result.push_str(&format!("{}[", Self::TEXT_COLOR.fg_str()));
+    self.visit_expression(&index_expression.index);
+    self.result.push_str(&format!("{}]", Self::TEXT_COLOR.fg_str()));
+  }
+
   fn visit_parenthesised_expression(&mut self, parenthesised_expression: &ParenthesisExpression) {
     self.result.push_str(&format!("{}{}", Self::TEXT_COLOR.fg_str(), "(", ));
     self.visit_expression(&parenthesised_expression.expression);
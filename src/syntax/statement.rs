@@ -1,28 +1,79 @@
+use std::rc::Rc;
+
 use super::{
   expression::SyntaxTreeExpression, 
-  lexer::Token, 
-  types::_let::LetStatement
+  lexer::{TextSpan, Token}, 
+  types::{
+    _for::ForStatement,
+    _function::FunctionDeclaration,
+    _let::LetStatement,
+    _return::ReturnStatement,
+    _struct::StructDeclaration,
+    _while::WhileStatement
+  }
 };
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum SyntaxTreeStatementKind {
   Expression(SyntaxTreeExpression),
-  Let(LetStatement)
+  Let(LetStatement),
+  Function(FunctionDeclaration),
+  Return(ReturnStatement),
+  Struct(StructDeclaration),
+  While(WhileStatement),
+  For(ForStatement),
+  // The `break` and `continue` keywords
+  Break(Token),
+  Continue(Token)
 }
 
+// The span covers the statement itself, not the terminator after it
+#[derive(Debug, Clone, PartialEq)]
 pub struct SyntaxTreeStatement {
-  pub kind: SyntaxTreeStatementKind
+  pub kind: SyntaxTreeStatementKind,
+  pub span: TextSpan
 }
 
 impl SyntaxTreeStatement {
-  pub fn new(kind: SyntaxTreeStatementKind) -> Self {
-    return SyntaxTreeStatement { kind };
+  pub fn new(kind: SyntaxTreeStatementKind, span: TextSpan) -> Self {
+    return SyntaxTreeStatement { kind, span };
   }
 
-  pub fn expression(expr: SyntaxTreeExpression) -> Self {
-    return SyntaxTreeStatement::new(SyntaxTreeStatementKind::Expression(expr));
+  pub fn expression(expr: SyntaxTreeExpression, span: TextSpan) -> Self {
+    return SyntaxTreeStatement::new(SyntaxTreeStatementKind::Expression(expr), span);
   }
 
-  pub fn let_statement(identifier: Token, initialiser: SyntaxTreeExpression) -> Self {    
-    return SyntaxTreeStatement::new(SyntaxTreeStatementKind::Let(LetStatement { identifier, initialiser }));
+  pub fn let_statement(identifier: Token, mutable: bool, initialiser: SyntaxTreeExpression, span: TextSpan) -> Self {    
+    return SyntaxTreeStatement::new(SyntaxTreeStatementKind::Let(LetStatement { identifier, mutable, initialiser }), span);
   }
-}
\ No newline at end of file
+
+  pub fn function_declaration(identifier: Token, parameters: Vec<Token>, body: SyntaxTreeExpression, span: TextSpan) -> Self {
+    return SyntaxTreeStatement::new(SyntaxTreeStatementKind::Function(FunctionDeclaration { identifier, parameters, body: Rc::new(body) }), span);
+  }
+
+  pub fn return_statement(keyword: Token, value: Option<SyntaxTreeExpression>, span: TextSpan) -> Self {
+    return SyntaxTreeStatement::new(SyntaxTreeStatementKind::Return(ReturnStatement { keyword, value }), span);
+  }
+
+  pub fn struct_declaration(identifier: Token, fields: Vec<Token>, span: TextSpan) -> Self {
+    return SyntaxTreeStatement::new(SyntaxTreeStatementKind::Struct(StructDeclaration { identifier, fields }), span);
+  }
+
+  pub fn while_statement(condition: SyntaxTreeExpression, body: SyntaxTreeExpression, span: TextSpan) -> Self {
+    return SyntaxTreeStatement::new(SyntaxTreeStatementKind::While(WhileStatement { condition, body }), span);
+  }
+
+  pub fn for_statement(identifier: Token, iterable: SyntaxTreeExpression, body: SyntaxTreeExpression, span: TextSpan) -> Self {
+    return SyntaxTreeStatement::new(SyntaxTreeStatementKind::For(ForStatement { identifier, iterable, body }), span);
+  }
+
+  pub fn break_statement(keyword: Token) -> Self {
+    let span = keyword.span.clone();
+    return SyntaxTreeStatement::new(SyntaxTreeStatementKind::Break(keyword), span);
+  }
+
+  pub fn continue_statement(keyword: Token) -> Self {
+    let span = keyword.span.clone();
+    return SyntaxTreeStatement::new(SyntaxTreeStatementKind::Continue(keyword), span);
+  }
+}
@@ -1,26 +1,63 @@
 
 #[cfg(test)]
 mod test {
+  use std::{cell::RefCell, rc::Rc};
+
+  use num_bigint::BigInt;
+  use proptest::prelude::*;
+
   use crate::{
     compilation_unit::CompilationUnit, 
+    diagnostics::{Diagnostic, DiagnosticGlossary, DiagnosticGlossaryCell},
     syntax::{
-      lexer::TextSpan, types::{
-        _binary::BinaryExpression, _let::LetStatement, _number::NumberExpression, _parenthesis::ParenthesisExpression, _unary::UnaryExpression, _variable::VariableExpression
+      evaluator::Evaluator,
+      incremental::{self, Reparse},
+      lexer::{Lexer, StringSegment, TextSpan, Token, TokenType}, types::{
+        _array::ArrayExpression, _assignment::AssignmentExpression, _binary::BinaryExpression, _block::BlockExpression, _boolean::BooleanExpression, _call::CallExpression, _closure::ClosureExpression, _for::ForStatement, _function::FunctionDeclaration, _if::IfExpression, _index::IndexExpression, _let::LetStatement, _map::MapExpression, _number::{Number, NumberExpression}, _parenthesis::ParenthesisExpression, _return::ReturnStatement, _struct::{FieldExpression, StructDeclaration, StructLiteralExpression}, _string::{InterpolationExpression, InterpolationPart, StringExpression}, _unary::UnaryExpression, _variable::VariableExpression, _while::WhileStatement
       }, 
+      parser::Parser,
+      printer::SyntaxTreePrinter,
+      statement::SyntaxTreeStatementKind,
+      value::{RangeValue, Value},
       visitor::SyntaxTreeVisitor,
       SyntaxTree
-    }
+    },
+    text::TextEdit
   };
 
   
-  #[derive(Debug, PartialEq, Eq)]
+  #[derive(Debug, PartialEq)]
   enum SyntaxTreeTestNode {
     Number(i64),
+    Float(f64),
+    BigInteger(String),
     Binary,
     Unary,
     Parenthesised,
     LetStatement,
-    Variable(String)
+    Variable(String),
+    String(String),
+    Interpolation,
+    Boolean(bool),
+    Block,
+    If,
+    Assignment(String),
+    While,
+    For(String),
+    Break,
+    Continue,
+    Function(String),
+    Return,
+    Call,
+    Closure,
+    Array,
+    Index,
+    Map,
+    Struct(String),
+    StructLiteral(String),
+    Field(String),
+    Error,
+    Missing
   }
 
   struct SyntatTreeVerifier {
@@ -29,11 +66,17 @@ mod test {
   }
 
   impl SyntatTreeVerifier {
+    // Only lexes and parses, so trees with undeclared variables can be checked too
     pub fn new(input: &str, expected: Vec<SyntaxTreeTestNode>) -> Self {
-      let compilation_unit = CompilationUnit::compile(input);
-      assert_eq!(compilation_unit.diagnostic_glossary.borrow().diagnostics.len(), 0, "Expected no diagnostics, got {:?} instead", compilation_unit.diagnostic_glossary.borrow().diagnostics);
+      let diagnostics = Rc::new(RefCell::new(DiagnosticGlossary::new()));
+      let (_, syntax_tree) = parse_with(input, Rc::clone(&diagnostics));
+      assert_eq!(diagnostics.borrow().diagnostics.len(), 0, "Expected no diagnostics, got {:?} instead", diagnostics.borrow().diagnostics);
+      return Self::from_tree(&syntax_tree, expected);
+    }
+
+    pub fn from_tree(syntax_tree: &SyntaxTree, expected: Vec<SyntaxTreeTestNode>) -> Self {
       let mut verifier = SyntatTreeVerifier { expected, actual: Vec::new() };
-      verifier.flatten_syntax_tree(&compilation_unit.st);
+      verifier.flatten_syntax_tree(syntax_tree);
       return verifier;
     }
 
@@ -67,12 +110,151 @@ mod test {
     }
 
     fn visit_number(&mut self, number: &NumberExpression) {
-      self.actual.push(SyntaxTreeTestNode::Number(number.number));
+      self.actual.push(match number.number.clone() {
+        Number::Integer(integer) => SyntaxTreeTestNode::Number(integer),
+        Number::Float(float) => SyntaxTreeTestNode::Float(float),
+        Number::BigInteger(integer) => SyntaxTreeTestNode::BigInteger(integer.to_string())
+      });
+    }
+
+    fn visit_boolean(&mut self, boolean: &BooleanExpression) {
+      self.actual.push(SyntaxTreeTestNode::Boolean(boolean.value));
+    }
+
+    fn visit_while_statement(&mut self, while_statement: &WhileStatement) {
+      self.actual.push(SyntaxTreeTestNode::While);
+      self.visit_expression(&while_statement.condition);
+      self.visit_expression(&while_statement.body);
+    }
+
+    fn visit_for_statement(&mut self, for_statement: &ForStatement) {
+      self.actual.push(SyntaxTreeTestNode::For(for_statement.identifier.span.literal.clone()));
+      self.visit_expression(&for_statement.iterable);
+      self.visit_expression(&for_statement.body);
+    }
+
+    fn visit_break_statement(&mut self, _keyword: &Token) {
+      self.actual.push(SyntaxTreeTestNode::Break);
+    }
+
+    fn visit_continue_statement(&mut self, _keyword: &Token) {
+      self.actual.push(SyntaxTreeTestNode::Continue);
+    }
+
+    fn visit_function_declaration(&mut self, function_declaration: &FunctionDeclaration) {
+      self.actual.push(SyntaxTreeTestNode::Function(function_declaration.identifier.span.literal.clone()));
+      for parameter in &function_declaration.parameters {
+        self.actual.push(SyntaxTreeTestNode::Variable(parameter.span.literal.clone()));
+      }
+      self.visit_expression(&function_declaration.body);
+    }
+
+    fn visit_return_statement(&mut self, return_statement: &ReturnStatement) {
+      self.actual.push(SyntaxTreeTestNode::Return);
+      if let Some(value) = &return_statement.value {
+        self.visit_expression(value);
+      }
+    }
+
+    fn visit_call_expression(&mut self, call_expression: &CallExpression) {
+      self.actual.push(SyntaxTreeTestNode::Call);
+      self.visit_expression(&call_expression.callee);
+      for argument in &call_expression.arguments {
+        self.visit_expression(argument);
+      }
     }
 
-    fn visit_error(&mut self, span: &TextSpan) {
-      // Do Nothing 
-      todo!()
+    fn visit_closure_expression(&mut self, closure_expression: &ClosureExpression) {
+      self.actual.push(SyntaxTreeTestNode::Closure);
+      for parameter in &closure_expression.parameters {
+        self.actual.push(SyntaxTreeTestNode::Variable(parameter.span.literal.clone()));
+      }
+      self.visit_expression(&closure_expression.body);
+    }
+
+    fn visit_array_expression(&mut self, array_expression: &ArrayExpression) {
+      self.actual.push(SyntaxTreeTestNode::Array);
+      for element in &array_expression.elements {
+        self.visit_expression(element);
+      }
+    }
+
+    fn visit_map_expression(&mut self, map_expression: &MapExpression) {
+      self.actual.push(SyntaxTreeTestNode::Map);
+      for entry in &map_expression.entries {
+        self.visit_expression(&entry.key);
+        self.visit_expression(&entry.value);
+      }
+    }
+
+    fn visit_struct_declaration(&mut self, struct_declaration: &StructDeclaration) {
+      self.actual.push(SyntaxTreeTestNode::Struct(struct_declaration.identifier.span.literal.clone()));
+      for field in &struct_declaration.fields {
+        self.actual.push(SyntaxTreeTestNode::Field(field.span.literal.clone()));
+      }
+    }
+
+    fn visit_struct_literal_expression(&mut self, struct_literal_expression: &StructLiteralExpression) {
+      self.actual.push(SyntaxTreeTestNode::StructLiteral(struct_literal_expression.identifier.span.literal.clone()));
+      for initialiser in &struct_literal_expression.initialisers {
+        self.actual.push(SyntaxTreeTestNode::Field(initialiser.field.span.literal.clone()));
+        self.visit_expression(&initialiser.value);
+      }
+    }
+
+    fn visit_field_expression(&mut self, field_expression: &FieldExpression) {
+      self.actual.push(SyntaxTreeTestNode::Field(field_expression.field.span.literal.clone()));
+      self.visit_expression(&field_expression.target);
+    }
+
+    fn visit_index_expression(&mut self, index_expression: &IndexExpression) {
+      self.actual.push(SyntaxTreeTestNode::Index);
+      self.visit_expression(&index_expression.target);
+      self.visit_expression(&index_expression.index);
+    }
+
+    fn visit_assignment_expression(&mut self, assignment_expression: &AssignmentExpression) {
+      self.actual.push(SyntaxTreeTestNode::Assignment(assignment_expression.operator.token.span.literal.clone()));
+      self.visit_expression(&assignment_expression.target);
+      self.visit_expression(&assignment_expression.value);
+    }
+
+    fn visit_block_expression(&mut self, block_expression: &BlockExpression) {
+      self.actual.push(SyntaxTreeTestNode::Block);
+      for statement in &block_expression.statements {
+        self.visit_statement(statement);
+      }
+    }
+
+    fn visit_if_expression(&mut self, if_expression: &IfExpression) {
+      self.actual.push(SyntaxTreeTestNode::If);
+      self.visit_expression(&if_expression.condition);
+      self.visit_expression(&if_expression.then_branch);
+      if let Some(else_branch) = &if_expression.else_branch {
+        self.visit_expression(else_branch);
+      }
+    }
+
+    fn visit_string(&mut self, string: &StringExpression) {
+      self.actual.push(SyntaxTreeTestNode::String(string.value.clone()));
+    }
+
+    fn visit_interpolation_expression(&mut self, interpolation_expression: &InterpolationExpression) {
+      self.actual.push(SyntaxTreeTestNode::Interpolation);
+      for part in &interpolation_expression.parts {
+        match part {
+          InterpolationPart::Text(text) => self.actual.push(SyntaxTreeTestNode::String(text.clone())),
+          InterpolationPart::Expression(expr) => self.visit_expression(expr)
+        }
+      }
+    }
+
+    fn visit_error(&mut self, _span: &TextSpan) {
+      self.actual.push(SyntaxTreeTestNode::Error);
+    }
+
+    fn visit_missing(&mut self, _span: &TextSpan) {
+      self.actual.push(SyntaxTreeTestNode::Missing);
     }
 
     fn visit_parenthesised_expression(&mut self, parenthesised_expression: &ParenthesisExpression) {
@@ -97,6 +279,94 @@ mod test {
     verifier.verify();
   }
 
+  fn assert_tokens(input: &str, expected: Vec<TokenType>) {
+    let diagnostics = Rc::new(RefCell::new(DiagnosticGlossary::new()));
+    let actual: Vec<TokenType> = Lexer::new(input, Rc::clone(&diagnostics))
+      .filter(|token| !token.is_trivia() && token.kind != TokenType::Eof)
+      .map(|token| token.kind)
+      .collect();
+    assert_eq!(diagnostics.borrow().diagnostics.len(), 0, "Expected no diagnostics, got {:?} instead", diagnostics.borrow().diagnostics);
+    assert_eq!(expected, actual, "Unexpected tokens for '{}'", input);
+  }
+
+  fn lex_with_trivia(input: &str) -> Vec<Token> {
+    let diagnostics = Rc::new(RefCell::new(DiagnosticGlossary::new()));
+    let tokens: Vec<Token> = Lexer::new(input, Rc::clone(&diagnostics)).collect();
+    assert_eq!(diagnostics.borrow().diagnostics.len(), 0, "Expected no diagnostics, got {:?} instead", diagnostics.borrow().diagnostics);
+    return Token::attach_trivia(tokens);
+  }
+
+  // Lexing never fails, every byte ends up in a token and the stream ends with
+  // an empty EOF token at the end of input.
+  fn assert_lossless(input: &str) {
+    let diagnostics = Rc::new(RefCell::new(DiagnosticGlossary::new()));
+    let tokens: Vec<Token> = Lexer::new(input, Rc::clone(&diagnostics)).collect();
+    let source: String = tokens.iter().map(|token| token.span.literal.as_str()).collect();
+    assert_eq!(input, source, "Tokens do not rebuild the input");
+    let eof = tokens.last().unwrap();
+    assert_eq!(eof.kind, TokenType::Eof);
+    assert_eq!((eof.span.start, eof.span.end), (input.len(), input.len()));
+    assert_eq!(tokens.iter().filter(|token| token.kind == TokenType::Eof).count(), 1);
+  }
+
+  // Trees of invalid input, whatever diagnostics they come with
+  fn assert_recovered_tree(input: &str, expected: Vec<SyntaxTreeTestNode>) {
+    let (_, syntax_tree) = lex_and_parse(input);
+    SyntatTreeVerifier::from_tree(&syntax_tree, expected).verify();
+  }
+
+  fn lex_and_parse(input: &str) -> (Vec<Token>, SyntaxTree) {
+    return parse_with(input, Rc::new(RefCell::new(DiagnosticGlossary::new())));
+  }
+
+  fn parse_with(input: &str, diagnostics: DiagnosticGlossaryCell) -> (Vec<Token>, SyntaxTree) {
+    let tokens: Vec<Token> = Lexer::new(input, Rc::clone(&diagnostics)).collect();
+    let mut parser = Parser::new(tokens.clone(), diagnostics);
+    let mut tree = SyntaxTree::new();
+    while let Some(statement) = parser.next_statement() {
+      tree.add_statement(statement);
+    }
+    return (tokens, tree);
+  }
+
+  // Reparsing after an edit has to give the same tokens, tree and diagnostics
+  // as parsing the edited text from scratch.
+  fn assert_reparse(input: &str, edit: TextEdit) -> Reparse {
+    let diagnostics = Rc::new(RefCell::new(DiagnosticGlossary::new()));
+    let (tokens, tree) = parse_with(input, Rc::clone(&diagnostics));
+    let reparsed_diagnostics = Rc::new(RefCell::new(DiagnosticGlossary::new()));
+    let reparse = incremental::reparse(tokens, tree, &diagnostics.borrow().diagnostics, &edit, Rc::clone(&reparsed_diagnostics));
+    let expected_diagnostics = Rc::new(RefCell::new(DiagnosticGlossary::new()));
+    let (expected_tokens, expected_tree) = parse_with(&edit.apply(input), Rc::clone(&expected_diagnostics));
+    assert_eq!(expected_tokens, reparse.tokens, "Unexpected tokens after edit of '{}'", input);
+    assert_eq!(expected_tree.statements, reparse.tree.statements, "Unexpected tree after edit of '{}'", input);
+    assert_eq!(
+      diagnostic_spans(&expected_diagnostics.borrow().diagnostics), diagnostic_spans(&reparsed_diagnostics.borrow().diagnostics),
+      "Unexpected diagnostics after edit of '{}'", input
+    );
+    return reparse;
+  }
+
+  // Diagnostics in the order of their spans, which reparsing doesn't keep
+  fn diagnostic_spans(diagnostics: &[Diagnostic]) -> Vec<(usize, usize, String)> {
+    let mut spans: Vec<_> = diagnostics.iter()
+      .map(|diagnostic| (diagnostic.span.start, diagnostic.span.end, diagnostic.message.clone()))
+      .collect();
+    spans.sort();
+    return spans;
+  }
+
+  fn trivia_literals(trivia: &[Token]) -> Vec<&str> {
+    return trivia.iter().map(|token| token.span.literal.as_str()).collect();
+  }
+
+  fn assert_value(input: &str, expected: Value) {
+    let compilation_unit = CompilationUnit::compile(input);
+    let actual = compilation_unit.evaluate();
+    assert_eq!(compilation_unit.diagnostic_glossary.borrow().diagnostics.len(), 0, "Expected no diagnostics, got {:?} instead", compilation_unit.diagnostic_glossary.borrow().diagnostics);
+    assert_eq!(Some(expected), actual, "Unexpected result for '{}'", input);
+  }
+
   #[test]
   pub fn should_parse_basic_binary_expression() {
     let input = "let a = 1 + 2";
@@ -223,4 +493,981 @@ mod test {
     assert_tree(input, expected);
   }
 
-}
\ No newline at end of file
+  #[test]
+  pub fn should_parse_every_infix_operator() {
+    for operator in ["+", "-", "*", "/", "%", "**", "&", "|", "^", "<<", ">>", "==", "!=", "<", "<=", ">", ">=", "&&", "||", "..", "..="] {
+      let input = format!("let a = 1 {} 2", operator);
+      let expected = vec![
+        SyntaxTreeTestNode::LetStatement,
+        SyntaxTreeTestNode::Binary,
+        SyntaxTreeTestNode::Number(1),
+        SyntaxTreeTestNode::Number(2)
+      ];
+
+      assert_tree(&input, expected);
+    }
+  }
+
+  #[test]
+  pub fn should_parse_prefix_operators() {
+    let input = "let a = -~!1";
+    let expected = vec![
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Unary,
+      SyntaxTreeTestNode::Unary,
+      SyntaxTreeTestNode::Unary,
+      SyntaxTreeTestNode::Number(1)
+    ];
+
+    assert_tree(input, expected);
+  }
+
+  #[test]
+  pub fn should_parse_power_tighter_than_negation() {
+    let input = "let a = -2 ** 2";
+    let expected = vec![
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Unary,
+      SyntaxTreeTestNode::Binary,
+      SyntaxTreeTestNode::Number(2),
+      SyntaxTreeTestNode::Number(2)
+    ];
+
+    assert_tree(input, expected);
+  }
+
+  #[test]
+  pub fn should_apply_precedence_and_associativity() {
+    assert_value("10 - 4 - 3", Value::Integer(3));
+    assert_value("64 / 4 / 2", Value::Integer(8));
+    assert_value("2 ** 3 ** 2", Value::Integer(512));
+    assert_value("-2 ** 2", Value::Integer(-4));
+    assert_value("2 ** -1", Value::Float(0.5));
+    assert_value("1 + 2 * 3 % 4", Value::Integer(3));
+    assert_value("1 << 2 + 1", Value::Integer(8));
+    assert_value("6 & 3 | 8 ^ 1", Value::Integer(11));
+    assert_value("-7 % 3", Value::Integer(-1));
+    assert_value("-16 >> 2", Value::Integer(-4));
+  }
+
+  #[test]
+  pub fn should_lex_numeric_literals() {
+    let input = "2.75 1e-9 2.5E+3 0xFF 0b1010 0o17 1_000_000 42";
+    let expected = vec![
+      TokenType::Float(2.75),
+      TokenType::Float(1e-9),
+      TokenType::Float(2500.0),
+      TokenType::Number(255),
+      TokenType::Number(10),
+      TokenType::Number(15),
+      TokenType::Number(1_000_000),
+      TokenType::Number(42)
+    ];
+
+    assert_tokens(input, expected);
+  }
+
+  #[test]
+  pub fn should_lex_radix_prefix_without_digits_as_identifier() {
+    let input = "0x";
+    let expected = vec![
+      TokenType::Number(0),
+      TokenType::Identifier
+    ];
+
+    assert_tokens(input, expected);
+  }
+
+  #[test]
+  pub fn should_parse_float_literal() {
+    let input = "let a = 1.5 * 2";
+    let expected = vec![
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Binary,
+      SyntaxTreeTestNode::Float(1.5),
+      SyntaxTreeTestNode::Number(2)
+    ];
+
+    assert_tree(input, expected);
+  }
+
+  #[test]
+  pub fn should_evaluate_integer_arithmetic() {
+    assert_value("0xFF + 1", Value::Integer(256));
+    assert_value("7 / 2", Value::Integer(3));
+  }
+
+  #[test]
+  pub fn should_promote_mixed_arithmetic_to_float() {
+    assert_value("1.5 * 2", Value::Float(3.0));
+    assert_value("7 / 2.0", Value::Float(3.5));
+    assert_value("1e3 - 1", Value::Float(999.0));
+  }
+
+  #[test]
+  pub fn should_lex_string_escape_sequences() {
+    let input = r#""a\n\t\"\\\u{1F600}\${b}""#;
+    let expected = vec![
+      TokenType::String(vec![StringSegment::Text("a\n\t\"\\\u{1F600}${b}".to_string())])
+    ];
+
+    assert_tokens(input, expected);
+  }
+
+  #[test]
+  pub fn should_parse_string_interpolation() {
+    let input = r#"let a = "sum: ${1 + 2}!""#;
+    let expected = vec![
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Interpolation,
+      SyntaxTreeTestNode::String("sum: ".to_string()),
+      SyntaxTreeTestNode::Binary,
+      SyntaxTreeTestNode::Number(1),
+      SyntaxTreeTestNode::Number(2),
+      SyntaxTreeTestNode::String("!".to_string())
+    ];
+
+    assert_tree(input, expected);
+  }
+
+  #[test]
+  pub fn should_concatenate_strings() {
+    assert_value(r#""unit" + "lang""#, Value::String("unitlang".to_string()));
+    assert_value(r#""${"nested ${1.5 * 2}"} and ${7 / 2}""#, Value::String("nested 3.0 and 3".to_string()));
+  }
+
+  #[test]
+  pub fn should_parse_through_comments() {
+    let input = "// leading\nlet a = 1 /* nested /* block */ comment */ + 2 // trailing";
+    let expected = vec![
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Binary,
+      SyntaxTreeTestNode::Number(1),
+      SyntaxTreeTestNode::Number(2)
+    ];
+
+    assert_tree(input, expected);
+  }
+
+  #[test]
+  pub fn should_attach_comments_as_trivia() {
+    let input = "// doc\nlet a = 1 // note\n/* tail */";
+    let tokens = lex_with_trivia(input);
+    let kinds: Vec<&TokenType> = tokens.iter().map(|token| &token.kind).collect();
+    assert_eq!(kinds, vec![&TokenType::Let, &TokenType::Identifier, &TokenType::Equal, &TokenType::Number(1), &TokenType::NewLine, &TokenType::Eof]);
+
+    assert_eq!(trivia_literals(&tokens[0].leading_trivia), vec!["// doc", "\n"]);
+    assert_eq!(trivia_literals(&tokens[0].trailing_trivia), vec![" "]);
+    assert_eq!(trivia_literals(&tokens[3].trailing_trivia), vec![" ", "// note"]);
+    assert_eq!(trivia_literals(&tokens[5].leading_trivia), vec!["/* tail */"]);
+  }
+
+  #[test]
+  pub fn should_lex_identifiers_with_digits_and_underscores() {
+    let input = "x1 total_count _tmp π größe 変数";
+    let expected = vec![TokenType::Identifier; 6];
+
+    assert_tokens(input, expected);
+  }
+
+  #[test]
+  pub fn should_normalise_identifiers() {
+    // "é" spelled decomposed in the declaration and precomposed in the use
+    assert_value("let cafe\u{301} = 5\ncaf\u{e9} * 2", Value::Integer(10));
+  }
+
+  #[test]
+  pub fn should_lex_big_integer_literals() {
+    let input = "99999999999999999999n 0xFFn 12n";
+    let expected = vec![
+      TokenType::BigInteger("99999999999999999999".parse().unwrap()),
+      TokenType::BigInteger(BigInt::from(255)),
+      TokenType::BigInteger(BigInt::from(12))
+    ];
+
+    assert_tokens(input, expected);
+  }
+
+  #[test]
+  pub fn should_lex_out_of_range_literals_without_a_value() {
+    let input = "99999999999999999999 0xFFFFFFFFFFFFFFFFF 1e999";
+    let diagnostics = Rc::new(RefCell::new(DiagnosticGlossary::new()));
+    let actual: Vec<TokenType> = Lexer::new(input, Rc::clone(&diagnostics))
+      .filter(|token| !token.is_trivia() && token.kind != TokenType::Eof)
+      .map(|token| token.kind)
+      .collect();
+    assert_eq!(actual, vec![TokenType::BadNumber, TokenType::BadNumber, TokenType::BadNumber]);
+    assert_eq!(diagnostics.borrow().diagnostics.len(), 3);
+  }
+
+  #[test]
+  pub fn should_evaluate_big_integer_arithmetic() {
+    assert_value("99999999999999999999n * 10 + 9", Value::BigInteger("999999999999999999999".parse().unwrap()));
+    assert_value("9223372036854775807n + 1", Value::BigInteger(BigInt::from(1u64 << 63)));
+    assert_value("1n / 4.0", Value::Float(0.25));
+  }
+
+  #[test]
+  pub fn should_parse_boolean_literals() {
+    let input = "let a = true != !false";
+    let expected = vec![
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Binary,
+      SyntaxTreeTestNode::Boolean(true),
+      SyntaxTreeTestNode::Unary,
+      SyntaxTreeTestNode::Boolean(false),
+    ];
+
+    assert_tree(input, expected);
+  }
+
+  #[test]
+  pub fn should_evaluate_comparisons() {
+    assert_value("1 + 1 == 2", Value::Boolean(true));
+    assert_value("(1 < 2) == (2 < 1)", Value::Boolean(false));
+    assert_value("2 >= 1.5", Value::Boolean(true));
+    assert_value("99999999999999999999n > 1", Value::Boolean(true));
+    assert_value(r#""abc" <= "abd""#, Value::Boolean(true));
+    assert_value("true != false", Value::Boolean(true));
+    assert_value("0.0 / 0.0 == 0.0 / 0.0", Value::Boolean(false));
+  }
+
+  #[test]
+  pub fn should_short_circuit_logical_operators() {
+    assert_value("false && 1 / 0 == 1", Value::Boolean(false));
+    assert_value("true || 1 % 0 == 0", Value::Boolean(true));
+    assert_value("true && !false", Value::Boolean(true));
+    assert_value("false || 1 > 2", Value::Boolean(false));
+  }
+
+  #[test]
+  pub fn should_lex_multi_character_operators() {
+    let input = "== != < <= > >= && || ! << >> % += -= *= /= -> => :: .. ..= . , ; : { } [ ] ** & | =";
+    let expected = vec![
+      TokenType::DoubleEqual,
+      TokenType::BangEqual,
+      TokenType::LessThan,
+      TokenType::LessThanEqual,
+      TokenType::GreaterThan,
+      TokenType::GreaterThanEqual,
+      TokenType::DoubleAmpersand,
+      TokenType::DoublePipe,
+      TokenType::Bang,
+      TokenType::DoubleLessThan,
+      TokenType::DoubleGreaterThan,
+      TokenType::Percent,
+      TokenType::PlusEqual,
+      TokenType::MinusEqual,
+      TokenType::AsteriskEqual,
+      TokenType::ForwardSlashEqual,
+      TokenType::Arrow,
+      TokenType::FatArrow,
+      TokenType::DoubleColon,
+      TokenType::DoubleDot,
+      TokenType::DoubleDotEqual,
+      TokenType::Dot,
+      TokenType::Comma,
+      TokenType::SemiColon,
+      TokenType::Colon,
+      TokenType::LeftBrace,
+      TokenType::RightBrace,
+      TokenType::LeftBracket,
+      TokenType::RightBracket,
+      TokenType::DoubleAsterisk,
+      TokenType::Ampersand,
+      TokenType::Pipe,
+      TokenType::Equal
+    ];
+
+    assert_tokens(input, expected);
+  }
+
+  #[test]
+  pub fn should_not_lex_range_bounds_as_floats() {
+    let input = "0..10 1..=n x.y";
+    let expected = vec![
+      TokenType::Number(0),
+      TokenType::DoubleDot,
+      TokenType::Number(10),
+      TokenType::Number(1),
+      TokenType::DoubleDotEqual,
+      TokenType::Identifier,
+      TokenType::Identifier,
+      TokenType::Dot,
+      TokenType::Identifier
+    ];
+
+    assert_tokens(input, expected);
+  }
+
+  #[test]
+  pub fn should_terminate_statements_at_newlines_and_semicolons() {
+    let input = "let a = 1\n\nlet b = 2; let c = 3;\n";
+    let expected = vec![
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Number(1),
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Number(2),
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Number(3)
+    ];
+
+    assert_tree(input, expected);
+  }
+
+  #[test]
+  pub fn should_continue_statements_after_operators_and_inside_parentheses() {
+    let input = "let a = 1 +\n  2\nlet b = (3\n  * 4)";
+    let expected = vec![
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Binary,
+      SyntaxTreeTestNode::Number(1),
+      SyntaxTreeTestNode::Number(2),
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Parenthesised,
+      SyntaxTreeTestNode::Binary,
+      SyntaxTreeTestNode::Number(3),
+      SyntaxTreeTestNode::Number(4)
+    ];
+
+    assert_tree(input, expected);
+  }
+
+  #[test]
+  pub fn should_rebuild_input_from_tokens() {
+    assert_lossless("");
+    assert_lossless("let a = 1 + 2 // sum\r\nlet b = \"x ${a} y\" /* done */");
+    assert_lossless("let ｘ = 0x_ 1__2.e 3n @@ ≠ \"${\"");
+    assert_lossless("/* unterminated /* nested */");
+    assert_lossless("\"\\u{zz} \\q");
+  }
+
+  proptest! {
+    #[test]
+    fn should_rebuild_source_like_input(input in r#"[a-z0-9_ \t\r\n+\-*/%=!<>&|^~:.,;(){}\[\]\\"$@é€]{0,64}"#) {
+      assert_lossless(&input);
+    }
+
+    #[test]
+    fn should_parse_any_input_without_panicking(input in r#"(let|mut|if|else|while|break|continue|for|in|step|fn|return|struct|\+=|[a-z0-9 \n+\-*/%=!<>&|^~.,:;(){}\[\]"$@])*"#) {
+      lex_and_parse(&input);
+    }
+
+    #[test]
+    fn should_rebuild_any_input(input in any::<String>()) {
+      assert_lossless(&input);
+    }
+
+    #[test]
+    fn should_reparse_like_a_full_parse(
+      input in r#"(if |else |while |break|for |in |[a-z0-9_ \n+\-*/=;"$.(){}\[\]@]){0,48}"#,
+      start in any::<prop::sample::Index>(),
+      length in 0usize..8,
+      replacement in r#"[a-z0-9_ \n+\-*/=;"$.(){}\[\]@]{0,8}"#
+    ) {
+      let start = start.index(input.len() + 1);
+      assert_reparse(&input, TextEdit::new(start..(start + length).min(input.len()), &replacement));
+    }
+  }
+
+  #[test]
+  pub fn should_reparse_only_the_edited_statement() {
+    let input = "let a = 1\nlet b = 2\nlet c = a + b";
+    let reparse = assert_reparse(input, TextEdit::new(18..19, "20 * a"));
+    assert_eq!(reparse.reparsed, 1..2);
+    assert_eq!(reparse.relexed.len(), 8);
+    assert_eq!(reparse.tree.statements[2].span.literal, "let c = a + b");
+    assert_eq!((reparse.tree.statements[2].span.start, reparse.tree.statements[2].span.end), (25, 38));
+  }
+
+  #[test]
+  pub fn should_reparse_statements_joined_by_an_edit() {
+    let input = "let a = 1\nlet b = 2\nlet c = 3\nlet d = 4";
+    let reparse = assert_reparse(input, TextEdit::new(19..19, " +"));
+    assert_eq!(reparse.reparsed, 1..3);
+    assert_eq!(reparse.tree.statements.len(), 4);
+    let reparse = assert_reparse(input, TextEdit::new(17..18, "=\n"));
+    assert_eq!(reparse.reparsed, 1..2);
+  }
+
+  #[test]
+  pub fn should_relex_tokens_merged_or_split_by_an_edit() {
+    assert_reparse("let ab = 1 + 2", TextEdit::new(5..5, " "));
+    assert_reparse("let a = 1 . 5", TextEdit::new(9..10, ""));
+    assert_reparse("let a = 1.e", TextEdit::new(10..11, "5"));
+    assert_reparse("let a = 1e+x", TextEdit::new(11..12, "5"));
+    assert_reparse("let a = 0x\nlet b = 2", TextEdit::new(10..10, "1"));
+    assert_reparse("let a = 1\nlet b = 2", TextEdit::new(0..0, "/* "));
+    assert_reparse("let a = \"x\"\nlet b = 2", TextEdit::new(10..11, ""));
+    assert_reparse("let a = \"${1}\"\nlet b = 2", TextEdit::new(11..11, " + 2"));
+  }
+
+  #[test]
+  pub fn should_move_diagnostics_of_reused_statements_with_them() {
+    let input = "let a = 1\nlet b = )\nlet c = 3 @";
+    let diagnostics = Rc::new(RefCell::new(DiagnosticGlossary::new()));
+    let (tokens, tree) = parse_with(input, Rc::clone(&diagnostics));
+    let reparsed_diagnostics = Rc::new(RefCell::new(DiagnosticGlossary::new()));
+    let reparse = incremental::reparse(
+      tokens, tree, &diagnostics.borrow().diagnostics, &TextEdit::new(8..9, "100"), Rc::clone(&reparsed_diagnostics)
+    );
+    assert_eq!(reparse.reparsed, 0..1);
+    assert_eq!(diagnostic_spans(&reparsed_diagnostics.borrow().diagnostics), vec![
+      (20, 21, "Expected expression, found <)>".to_string()),
+      (32, 33, "Unexpected character '@' (U+0040)".to_string())
+    ]);
+  }
+
+  #[test]
+  pub fn should_reuse_everything_after_an_edit_in_trivia() {
+    let input = "let a = 1 // one\nlet b = 2\nlet c = 3";
+    let reparse = assert_reparse(input, TextEdit::new(13..16, "first"));
+    assert_eq!(reparse.reparsed, 0..1);
+  }
+
+  #[test]
+  pub fn should_recover_with_missing_and_error_nodes() {
+    let input = "let = (1 +\nlet b = ) 2\nlet c = 3";
+    let expected = vec![
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Parenthesised,
+      SyntaxTreeTestNode::Binary,
+      SyntaxTreeTestNode::Number(1),
+      SyntaxTreeTestNode::Missing,
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Missing,
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Number(3)
+    ];
+
+    assert_recovered_tree(input, expected);
+    assert_recovered_tree("let a = * 2", vec![SyntaxTreeTestNode::LetStatement, SyntaxTreeTestNode::Error]);
+  }
+
+  #[test]
+  pub fn should_parse_if_else_chains() {
+    let input = "let fee = if amount > 100 { 5 } else if amount > 50 {\n  let a = 7\n  a\n} else { 10 }";
+    let expected = vec![
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::If,
+      SyntaxTreeTestNode::Binary,
+      SyntaxTreeTestNode::Variable("amount".to_string()),
+      SyntaxTreeTestNode::Number(100),
+      SyntaxTreeTestNode::Block,
+      SyntaxTreeTestNode::Number(5),
+      SyntaxTreeTestNode::If,
+      SyntaxTreeTestNode::Binary,
+      SyntaxTreeTestNode::Variable("amount".to_string()),
+      SyntaxTreeTestNode::Number(50),
+      SyntaxTreeTestNode::Block,
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Number(7),
+      SyntaxTreeTestNode::Variable("a".to_string()),
+      SyntaxTreeTestNode::Block,
+      SyntaxTreeTestNode::Number(10)
+    ];
+
+    assert_tree(input, expected);
+  }
+
+  #[test]
+  pub fn should_evaluate_if_expressions() {
+    assert_value("let amount = 150\nif amount > 100 { 5 } else { 10 }", Value::Integer(5));
+    assert_value("let amount = 75\nif amount > 100 { 5 } else if amount > 50 { 7 } else { 10 }", Value::Integer(7));
+    assert_value("if false { 5 } else if false { 7 } else {\n  let a = 10\n  a * 2\n}", Value::Integer(20));
+    assert_value("if false { 5 }", Value::Unit);
+    assert_value("if true { let a = 5 }", Value::Unit);
+    assert_value("if false { 1 / 0 } else { 1 }", Value::Integer(1));
+  }
+
+  #[test]
+  pub fn should_scope_bindings_to_branches() {
+    assert_value("let a = 1\nif true { let a = 2\n a }\na", Value::Integer(1));
+    assert_value("let a = 1\nif true { let a = a + 1\n a } else { a }", Value::Integer(2));
+  }
+
+  #[test]
+  pub fn should_recover_from_incomplete_if_expressions() {
+    let expected = vec![
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::If,
+      SyntaxTreeTestNode::Variable("a".to_string()),
+      SyntaxTreeTestNode::Block,
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Number(2)
+    ];
+    assert_recovered_tree("let b = if a\nlet c = 2", expected);
+    assert_recovered_tree("if a { 1 2 }\n3", vec![
+      SyntaxTreeTestNode::If,
+      SyntaxTreeTestNode::Variable("a".to_string()),
+      SyntaxTreeTestNode::Block,
+      SyntaxTreeTestNode::Number(1),
+      SyntaxTreeTestNode::Number(3)
+    ]);
+  }
+
+  #[test]
+  pub fn should_reparse_inside_a_block() {
+    let input = "let a = 1\nlet b = if a > 0 {\n  a + 1\n} else { 0 }\nlet c = b";
+    let reparse = assert_reparse(input, TextEdit::new(30..31, "2"));
+    assert_eq!(reparse.reparsed, 1..2);
+    assert_eq!(reparse.tree.statements[2].span.literal, "let c = b");
+  }
+
+  #[test]
+  pub fn should_parse_while_loops() {
+    let input = "while a < 10 {\n  if a == 5 { break }\n  continue\n}";
+    let expected = vec![
+      SyntaxTreeTestNode::While,
+      SyntaxTreeTestNode::Binary,
+      SyntaxTreeTestNode::Variable("a".to_string()),
+      SyntaxTreeTestNode::Number(10),
+      SyntaxTreeTestNode::Block,
+      SyntaxTreeTestNode::If,
+      SyntaxTreeTestNode::Binary,
+      SyntaxTreeTestNode::Variable("a".to_string()),
+      SyntaxTreeTestNode::Number(5),
+      SyntaxTreeTestNode::Block,
+      SyntaxTreeTestNode::Break,
+      SyntaxTreeTestNode::Continue
+    ];
+
+    assert_recovered_tree(input, expected);
+  }
+
+  #[test]
+  pub fn should_evaluate_while_loops() {
+    assert_value("while false { 1 }", Value::Unit);
+    assert_value("let a = 1\nwhile a > 0 {\n  if a == 1 { break }\n}\na", Value::Integer(1));
+    assert_value("while true {\n  let b = if true { break } else { 2 }\n  1 / 0\n}", Value::Unit);
+  }
+
+  #[test]
+  pub fn should_parse_for_loops_over_ranges() {
+    let input = "for i in 0..=10 step 2 { i }";
+    let expected = vec![
+      SyntaxTreeTestNode::For("i".to_string()),
+      SyntaxTreeTestNode::Binary,
+      SyntaxTreeTestNode::Binary,
+      SyntaxTreeTestNode::Number(0),
+      SyntaxTreeTestNode::Number(10),
+      SyntaxTreeTestNode::Number(2),
+      SyntaxTreeTestNode::Block,
+      SyntaxTreeTestNode::Variable("i".to_string())
+    ];
+
+    assert_tree(input, expected);
+  }
+
+  #[test]
+  pub fn should_iterate_ranges() {
+    let values = |range: RangeValue| range.values().collect::<Vec<i64>>();
+    assert_eq!(values(RangeValue::new(0, 4, false)), vec![0, 1, 2, 3]);
+    assert_eq!(values(RangeValue::new(0, 4, true)), vec![0, 1, 2, 3, 4]);
+    assert_eq!(values(RangeValue { step: 3, ..RangeValue::new(0, 9, false) }), vec![0, 3, 6]);
+    assert_eq!(values(RangeValue { step: -3, ..RangeValue::new(10, 1, true) }), vec![10, 7, 4, 1]);
+    assert_eq!(values(RangeValue::new(4, 0, false)), vec![]);
+    assert_eq!(values(RangeValue::new(i64::MAX - 1, i64::MAX, true)), vec![i64::MAX - 1, i64::MAX]);
+  }
+
+  #[test]
+  pub fn should_evaluate_for_loops() {
+    assert_value("0..=10 step 5", Value::Range(RangeValue { step: 5, ..RangeValue::new(0, 10, true) }));
+    assert_value("for i in 0..10 {\n  if i == 3 { break }\n  i / (i - 5)\n}", Value::Unit);
+    assert_value("for i in 10..0 step -1 {\n  if i == 0 { 1 / 0 }\n}", Value::Unit);
+    assert_value("let i = 7\nfor i in 0..3 { i }\ni", Value::Integer(7));
+  }
+
+  #[test]
+  pub fn should_parse_blocks_as_expressions() {
+    let input = "let a = {\n  let b = 1\n  b + 1\n}\n{ }";
+    let expected = vec![
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Block,
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Number(1),
+      SyntaxTreeTestNode::Binary,
+      SyntaxTreeTestNode::Variable("b".to_string()),
+      SyntaxTreeTestNode::Number(1),
+      SyntaxTreeTestNode::Block
+    ];
+
+    assert_tree(input, expected);
+  }
+
+  #[test]
+  pub fn should_shadow_bindings_of_enclosing_scopes() {
+    assert_value("let a = 1\nlet b = {\n  let a = a + 10\n  a * 2\n}\na + b", Value::Integer(23));
+    assert_value("let a = 1\nlet a = a + 1\na", Value::Integer(2));
+    assert_value("{\n  let a = 1\n  { let a = 2 }\n  a\n}", Value::Integer(1));
+    assert_value("let a = { }", Value::Unit);
+  }
+
+  #[test]
+  pub fn should_parse_assignments_right_associative() {
+    let input = "let mut a = 1\na += b = 2 * 3";
+    let expected = vec![
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Number(1),
+      SyntaxTreeTestNode::Assignment("+=".to_string()),
+      SyntaxTreeTestNode::Variable("a".to_string()),
+      SyntaxTreeTestNode::Assignment("=".to_string()),
+      SyntaxTreeTestNode::Variable("b".to_string()),
+      SyntaxTreeTestNode::Binary,
+      SyntaxTreeTestNode::Number(2),
+      SyntaxTreeTestNode::Number(3)
+    ];
+
+    assert_tree(input, expected);
+    let (_, tree) = lex_and_parse(input);
+    let SyntaxTreeStatementKind::Let(let_statement) = &tree.statements[0].kind else {
+      panic!("Expected a let statement");
+    };
+    assert!(let_statement.mutable);
+  }
+
+  #[test]
+  pub fn should_evaluate_assignments() {
+    assert_value("let mut a = 1\na = a + 1\na", Value::Integer(2));
+    assert_value("let mut a = 10\na += 5\na -= 3\na *= 2\na /= 4", Value::Integer(6));
+    assert_value("let mut a = 1\nlet mut b = 2\na = b = 3\na + b", Value::Integer(6));
+    assert_value("let mut s = \"a\"\ns += \"b\"\ns", Value::String("ab".to_string()));
+    assert_value("let mut a = 1\n{ a = 2 }\na", Value::Integer(2));
+    assert_value("let mut a = 1\n{\n  let mut a = 5\n  a = 6\n}\na", Value::Integer(1));
+  }
+
+  #[test]
+  pub fn should_run_loops_over_mutable_state() {
+    assert_value("let mut i = 0\nwhile i < 10 { i += 1 }\ni", Value::Integer(10));
+    assert_value("let mut i = 0\nlet mut odd = 0\nwhile i < 10 {\n  i += 1\n  if i % 2 == 0 { continue }\n  odd += 1\n}\nodd", Value::Integer(5));
+    assert_value("let mut total = 0\nfor i in 1..=100 { total += i }\ntotal", Value::Integer(5050));
+    assert_value("let mut total = 0\nfor i in 10..0 step -3 {\n  if i < 4 { break }\n  total += i\n}\ntotal", Value::Integer(21));
+  }
+
+  #[test]
+  pub fn should_parse_function_declarations_and_calls() {
+    let input = "fn add(a, b) {\n  return a + b\n}\nadd(1, add(2, 3),)";
+    let expected = vec![
+      SyntaxTreeTestNode::Function("add".to_string()),
+      SyntaxTreeTestNode::Variable("a".to_string()),
+      SyntaxTreeTestNode::Variable("b".to_string()),
+      SyntaxTreeTestNode::Block,
+      SyntaxTreeTestNode::Return,
+      SyntaxTreeTestNode::Binary,
+      SyntaxTreeTestNode::Variable("a".to_string()),
+      SyntaxTreeTestNode::Variable("b".to_string()),
+      SyntaxTreeTestNode::Call,
+      SyntaxTreeTestNode::Variable("add".to_string()),
+      SyntaxTreeTestNode::Number(1),
+      SyntaxTreeTestNode::Call,
+      SyntaxTreeTestNode::Variable("add".to_string()),
+      SyntaxTreeTestNode::Number(2),
+      SyntaxTreeTestNode::Number(3)
+    ];
+
+    assert_tree(input, expected);
+  }
+
+  #[test]
+  pub fn should_evaluate_function_calls() {
+    assert_value("fn add(a, b) { a + b }\nadd(2, 3) * 2", Value::Integer(10));
+    assert_value("fn unit() { }\nunit()", Value::Unit);
+    assert_value("fn sign(n) {\n  if n < 0 { return -1 }\n  if n == 0 { return 0 }\n  1\n}\nsign(-5) + sign(0) + sign(7)", Value::Integer(0));
+    assert_value("fn first_over(limit) {\n  for i in 0..100 {\n    if i * i > limit { return i }\n  }\n  -1\n}\nfirst_over(50)", Value::Integer(8));
+    assert_value("fn factorial(n) { if n <= 1 { 1 } else { n * factorial(n - 1) } }\nfactorial(10)", Value::Integer(3628800));
+    assert_value("let rate = 3\nfn scale(x) { x * rate }\nscale(scale(2))", Value::Integer(18));
+    assert_value("let x = 1\nfn f(x) { x + 1 }\nf(10) + x", Value::Integer(12));
+    assert_value("let mut calls = 0\nfn count() { calls += 1 }\ncount()\ncount()\ncalls", Value::Integer(2));
+  }
+
+  #[test]
+  pub fn should_call_functions_declared_later() {
+    let even_odd = "fn even(n) { if n == 0 { true } else { odd(n - 1) } }\nfn odd(n) { if n == 0 { false } else { even(n - 1) } }";
+    assert_value(&format!("{}\neven(10)", even_odd), Value::Boolean(true));
+    assert_value(&format!("let a = odd(7)\n{}\na", even_odd), Value::Boolean(true));
+  }
+
+  #[test]
+  pub fn should_display_function_values() {
+    let compilation_unit = CompilationUnit::compile("fn add(a, b) { a + b }\nadd");
+    let actual = compilation_unit.evaluate().map(|value| value.to_string());
+    assert_eq!(actual, Some("<fn add>".to_string()));
+  }
+
+  #[test]
+  pub fn should_parse_closures() {
+    let input = "let add = |x, y| x + y\nlet zero = || 0\napply(|x| x * 2, 3)";
+    let expected = vec![
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Closure,
+      SyntaxTreeTestNode::Variable("x".to_string()),
+      SyntaxTreeTestNode::Variable("y".to_string()),
+      SyntaxTreeTestNode::Binary,
+      SyntaxTreeTestNode::Variable("x".to_string()),
+      SyntaxTreeTestNode::Variable("y".to_string()),
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Closure,
+      SyntaxTreeTestNode::Number(0),
+      SyntaxTreeTestNode::Call,
+      SyntaxTreeTestNode::Variable("apply".to_string()),
+      SyntaxTreeTestNode::Closure,
+      SyntaxTreeTestNode::Variable("x".to_string()),
+      SyntaxTreeTestNode::Binary,
+      SyntaxTreeTestNode::Variable("x".to_string()),
+      SyntaxTreeTestNode::Number(2),
+      SyntaxTreeTestNode::Number(3)
+    ];
+
+    assert_tree(input, expected);
+  }
+
+  #[test]
+  pub fn should_evaluate_closures() {
+    assert_value("let offset = 10\nlet add = |x| x + offset\nadd(5)", Value::Integer(15));
+    assert_value("let twice = |f, x| f(f(x))\ntwice(|x| x * 3, 2)", Value::Integer(18));
+    assert_value("fn adder(n) { |x| x + n }\nlet add2 = adder(2)\nlet add5 = adder(5)\nadd2(1) * add5(1)", Value::Integer(18));
+    assert_value("fn compose(f, g) { |x| g(f(x)) }\ncompose(|x| x + 1, |x| x * 10)(4)", Value::Integer(50));
+    assert_value("let f = || { return 1\n2 }\nf()", Value::Integer(1));
+    assert_value("fn apply(f) { f() }\nlet greeting = \"hi\"\napply(|| greeting + \"!\")", Value::String("hi!".to_string()));
+  }
+
+  #[test]
+  pub fn should_capture_locals_by_value() {
+    assert_value("let f = {\n  let mut n = 1\n  let get = || n\n  n = 2\n  get\n}\nf()", Value::Integer(1));
+    assert_value("let offset = 1\nlet add = |x| x + offset\nlet offset = 100\nadd(1)", Value::Integer(2));
+    assert_value("let mut offset = 1\nlet add = |x| x + offset\noffset = 100\nadd(1)", Value::Integer(2));
+    assert_value("let mut sum = 0\nfor i in 1..=3 {\n  let f = || i * 10\n  sum += f()\n}\nsum", Value::Integer(60));
+  }
+
+  // Printed tree without the colour escapes
+  fn print_plain(input: &str) -> String {
+    let (_, tree) = lex_and_parse(input);
+    let mut printer = SyntaxTreePrinter::new();
+    tree.visit(&mut printer);
+    let mut plain = String::new();
+    let mut escape = false;
+    for character in printer.result.chars() {
+      match character {
+        '\x1b' => escape = true,
+        'm' if escape => escape = false,
+        _ if !escape => plain.push(character),
+        _ => {}
+      }
+    }
+    return plain;
+  }
+
+  #[test]
+  pub fn should_print_closures() {
+    assert_eq!(print_plain("let add = |x, y| x + y"), "let add = |x, y| x + y\n");
+  }
+
+  #[test]
+  pub fn should_run_deep_tail_recursion_in_constant_stack() {
+    assert_value("fn count(n, total) { if n == 0 { total } else { count(n - 1, total + 1) } }\ncount(1000000, 0)", Value::Integer(1000000));
+    assert_value("fn count(n) {\n  if n == 0 { return \"done\" }\n  return count(n - 1)\n}\ncount(1000000)", Value::String("done".to_string()));
+    assert_value("fn apply(f, n) { f(n) }\nfn down(n) { if n == 0 { 0 } else { apply(down, n - 1) } }\ndown(100000)", Value::Integer(0));
+  }
+
+  #[test]
+  pub fn should_keep_non_tail_calls_nested() {
+    assert_value("fn sum(n) { if n == 0 { 0 } else { n + sum(n - 1) } }\nsum(200)", Value::Integer(20100));
+    assert_value("fn f(n) { let a = if n == 0 { 0 } else { f(n - 1) }\na + 1 }\nf(10)", Value::Integer(11));
+  }
+
+  #[test]
+  pub fn should_parse_arrays_and_indexing() {
+    let input = "let mut xs = [1, [2, 3],]\nxs[1][0] = xs[0..-1]";
+    let expected = vec![
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Array,
+      SyntaxTreeTestNode::Number(1),
+      SyntaxTreeTestNode::Array,
+      SyntaxTreeTestNode::Number(2),
+      SyntaxTreeTestNode::Number(3),
+      SyntaxTreeTestNode::Assignment("=".to_string()),
+      SyntaxTreeTestNode::Index,
+      SyntaxTreeTestNode::Index,
+      SyntaxTreeTestNode::Variable("xs".to_string()),
+      SyntaxTreeTestNode::Number(1),
+      SyntaxTreeTestNode::Number(0),
+      SyntaxTreeTestNode::Index,
+      SyntaxTreeTestNode::Variable("xs".to_string()),
+      SyntaxTreeTestNode::Binary,
+      SyntaxTreeTestNode::Number(0),
+      SyntaxTreeTestNode::Unary,
+      SyntaxTreeTestNode::Number(1)
+    ];
+
+    assert_tree(input, expected);
+  }
+
+  #[test]
+  pub fn should_index_and_slice_arrays() {
+    let array = |values: &[i64]| Value::Array(Rc::new(values.iter().map(|value| Value::Integer(*value)).collect()));
+    assert_value("let xs = [10, 20, 30]\nxs[0] + xs[2]", Value::Integer(40));
+    assert_value("let xs = [10, 20, 30]\nxs[-1] - xs[-3]", Value::Integer(20));
+    assert_value("let xs = [10, 20, 30, 40]\nxs[1..3]", array(&[20, 30]));
+    assert_value("let xs = [10, 20, 30, 40]\nxs[1..=-1]", array(&[20, 30, 40]));
+    assert_value("let xs = [10, 20, 30, 40]\nxs[2..2]", array(&[]));
+    assert_value("[[1, 2], [3, 4]][1][0]", Value::Integer(3));
+    assert_value("len([1, 2, 3]) + len(\"héllo\") + len([])", Value::Integer(8));
+    assert_value("[1, 2] + [3] == [1, 2, 3]", Value::Boolean(true));
+  }
+
+  #[test]
+  pub fn should_assign_to_elements() {
+    let array = |values: &[i64]| Value::Array(Rc::new(values.iter().map(|value| Value::Integer(*value)).collect()));
+    assert_value("let mut xs = [1, 2, 3]\nxs[0] = 5\nxs[-1] *= 10\nxs", array(&[5, 2, 30]));
+    assert_value("let mut grid = [[0, 0], [0, 0]]\ngrid[1][0] = 7\ngrid[1]", array(&[7, 0]));
+    assert_value("let mut xs = [1, 2]\nlet ys = xs\nxs[0] = 9\nys", array(&[1, 2]));
+    assert_value("let mut total = 0\nfor x in [3, 4, 5] { total += x }\ntotal", Value::Integer(12));
+    assert_value("let mut squares = []\nfor i in 0..4 { squares += [i * i] }\nsquares", array(&[0, 1, 4, 9]));
+  }
+
+  #[test]
+  pub fn should_display_arrays() {
+    let compilation_unit = CompilationUnit::compile("[1, \"a, b\", [true], len]");
+    let actual = compilation_unit.evaluate().map(|value| value.to_string());
+    assert_eq!(actual, Some("[1, \"a, b\", [true], <fn len>]".to_string()));
+  }
+
+  #[test]
+  pub fn should_parse_map_literals_apart_from_blocks() {
+    let input = "let m = {\n  \"a\": 1,\n  key: { 2 }\n}\nlet e = {:}\nlet b = { a }";
+    let expected = vec![
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Map,
+      SyntaxTreeTestNode::String("a".to_string()),
+      SyntaxTreeTestNode::Number(1),
+      SyntaxTreeTestNode::Variable("key".to_string()),
+      SyntaxTreeTestNode::Block,
+      SyntaxTreeTestNode::Number(2),
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Map,
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::Block,
+      SyntaxTreeTestNode::Variable("a".to_string())
+    ];
+
+    assert_tree(input, expected);
+  }
+
+  #[test]
+  pub fn should_look_up_and_insert_map_entries() {
+    assert_value("let m = { \"a\": 1, \"b\": 2 }\nm[\"a\"] + m[\"b\"]", Value::Integer(3));
+    assert_value("let mut m = {:}\nm[\"x\"] = 1\nm[\"x\"] += 4\nm[\"x\"]", Value::Integer(5));
+    assert_value("let mut m = { 1: [0, 0] }\nm[1][1] = 7\nm[1]", Value::Array(Rc::new(vec![Value::Integer(0), Value::Integer(7)])));
+    assert_value("let m = { true: \"yes\", 2: \"two\" }\nm[1 == 1] + m[2]", Value::String("yestwo".to_string()));
+    assert_value("let m = { \"a\": 1 }\ncontains(m, \"a\") && !contains(m, \"b\") && contains([1, 2], 2) && contains(\"abc\", \"bc\")", Value::Boolean(true));
+    assert_value("let mut m = { \"a\": 1 }\nlet n = m\nm[\"a\"] = 2\nn[\"a\"]", Value::Integer(1));
+    assert_value("{ \"a\": 1, \"b\": 2 } == { \"b\": 2, \"a\": 1 }", Value::Boolean(true));
+    assert_value("let mut m = { 1: \"one\" }\nm[2n] = \"two\"\nm[1n] + m[2] + \"${len(m)}\"", Value::String("onetwo2".to_string()));
+    assert_value("contains({ 5: 0 }, 5n) && { 7n: 1 } == { 7: 1 }", Value::Boolean(true));
+  }
+
+  #[test]
+  pub fn should_compare_numbers_in_collections_like_equality() {
+    assert_value("1 == 1.0 && [1] == [1.0] && [[2, 3.5]] == [[2.0, 3.5]]", Value::Boolean(true));
+    assert_value("{ \"a\": 1 } == { \"a\": 1.0 } && { \"a\": [1n] } == { \"a\": [1] }", Value::Boolean(true));
+    assert_value("struct Point { x, y }\nPoint { x: 1, y: 2.0 } == Point { x: 1.0, y: 2 }", Value::Boolean(true));
+    assert_value("contains([1], 1.0) && contains([1.5, 2], 2n) && !contains([1], 1.5)", Value::Boolean(true));
+    assert_value("[1] != [1.5] && [1, \"a\"] != [1, \"b\"] && [1] != [1, 2] && [0.0 / 0.0] != [0.0 / 0.0]", Value::Boolean(true));
+  }
+
+  #[test]
+  pub fn should_iterate_maps_in_insertion_order() {
+    let strings = |values: &[&str]| Value::Array(Rc::new(values.iter().map(|value| Value::String(value.to_string())).collect()));
+    assert_value("let mut m = { \"z\": 1, \"a\": 2 }\nm[\"m\"] = 3\nm[\"z\"] = 4\nkeys(m)", strings(&["z", "a", "m"]));
+    assert_value("let m = { \"z\": 1, \"a\": 2, \"m\": 3 }\nvalues(m)", Value::Array(Rc::new(vec![Value::Integer(1), Value::Integer(2), Value::Integer(3)])));
+    assert_value("let mut order = \"\"\nfor key in { \"c\": 1, \"a\": 2, \"b\": 3 } { order += key }\norder", Value::String("cab".to_string()));
+
+    let compilation_unit = CompilationUnit::compile("let mut m = { \"b\": [1], 2: true }\nm[\"a\"] = {:}\nm");
+    let actual = compilation_unit.evaluate().map(|value| value.to_string());
+    assert_eq!(actual, Some("{\"b\": [1], 2: true, \"a\": {:}}".to_string()));
+  }
+
+  #[test]
+  pub fn should_parse_struct_literals_apart_from_condition_blocks() {
+    let input = "struct Point {\n  x,\n  y,\n}\nlet p = Point { x: 1, y: (Point { x: 2, y: 3 }).x }\nif p.x == p { p.y }";
+    let expected = vec![
+      SyntaxTreeTestNode::Struct("Point".to_string()),
+      SyntaxTreeTestNode::Field("x".to_string()),
+      SyntaxTreeTestNode::Field("y".to_string()),
+      SyntaxTreeTestNode::LetStatement,
+      SyntaxTreeTestNode::StructLiteral("Point".to_string()),
+      SyntaxTreeTestNode::Field("x".to_string()),
+      SyntaxTreeTestNode::Number(1),
+      SyntaxTreeTestNode::Field("y".to_string()),
+      SyntaxTreeTestNode::Field("x".to_string()),
+      SyntaxTreeTestNode::Parenthesised,
+      SyntaxTreeTestNode::StructLiteral("Point".to_string()),
+      SyntaxTreeTestNode::Field("x".to_string()),
+      SyntaxTreeTestNode::Number(2),
+      SyntaxTreeTestNode::Field("y".to_string()),
+      SyntaxTreeTestNode::Number(3),
+      SyntaxTreeTestNode::If,
+      SyntaxTreeTestNode::Binary,
+      SyntaxTreeTestNode::Field("x".to_string()),
+      SyntaxTreeTestNode::Variable("p".to_string()),
+      SyntaxTreeTestNode::Variable("p".to_string()),
+      SyntaxTreeTestNode::Block,
+      SyntaxTreeTestNode::Field("y".to_string()),
+      SyntaxTreeTestNode::Variable("p".to_string())
+    ];
+
+    assert_tree(input, expected);
+  }
+
+  #[test]
+  pub fn should_construct_structs_and_read_fields() {
+    assert_value("struct Point { x, y }\nlet p = Point { y: 2, x: 1 }\np.x * 10 + p.y", Value::Integer(12));
+    assert_value("struct Line { from, to }\nstruct Point { x, y }\nlet l = Line { from: Point { x: 0, y: 0 }, to: Point { x: 3, y: 4 } }\nl.to.x + l.to.y", Value::Integer(7));
+    assert_value("struct Point { x, y }\nfn origin() { Point { x: 0, y: 0 } }\norigin() == Point { x: 0, y: 0 }", Value::Boolean(true));
+    assert_value("struct Pair { items }\nlet p = Pair { items: [1, 2] }\np.items[1]", Value::Integer(2));
+  }
+
+  #[test]
+  pub fn should_update_fields() {
+    assert_value("struct Point { x, y }\nlet mut p = Point { x: 1, y: 2 }\np.x = 5\np.y *= 3\np.x + p.y", Value::Integer(11));
+    assert_value("struct Point { x, y }\nlet mut p = Point { x: 1, y: 2 }\nlet q = p\np.x = 9\nq.x", Value::Integer(1));
+    assert_value("struct Box { items }\nlet mut b = Box { items: [0, 0] }\nb.items[1] = 4\nb.items", Value::Array(Rc::new(vec![Value::Integer(0), Value::Integer(4)])));
+    assert_value("struct Point { x }\nlet mut points = [Point { x: 1 }]\npoints[0].x += 1\npoints[0].x", Value::Integer(2));
+  }
+
+  #[test]
+  pub fn should_display_structs() {
+    let compilation_unit = CompilationUnit::compile("struct Empty {}\nstruct Tag { name, value, empty }\nTag { value: [1], name: \"a\", empty: Empty {} }");
+    let actual = compilation_unit.evaluate().map(|value| value.to_string());
+    assert_eq!(actual, Some("Tag { name: \"a\", value: [1], empty: Empty {} }".to_string()));
+  }
+
+  #[test]
+  pub fn should_print_structs() {
+    let input = "struct Point { x, y }\nstruct Empty {}\nlet mut p = Point { x: 1, y: Empty {} }\np.x = p.x + 1";
+    assert_eq!(print_plain(input), "struct Point { x, y }\nstruct Empty {}\nlet mut p = Point { x: 1, y: Empty {} }\np.x = p.x + 1\n");
+  }
+
+  #[test]
+  pub fn should_report_undeclared_variables_at_runtime_instead_of_panicking() {
+    let diagnostics = Rc::new(RefCell::new(DiagnosticGlossary::new()));
+    let (_, tree) = parse_with("let a = 1\nmissing + a", Rc::clone(&diagnostics));
+    let mut evaluator = Evaluator::new(Rc::clone(&diagnostics));
+    tree.visit(&mut evaluator);
+    assert_eq!(evaluator.last_value, None);
+    let diagnostics = diagnostics.borrow();
+    assert_eq!(diagnostics.diagnostics.len(), 1);
+    assert_eq!(diagnostics.diagnostics[0].message, "Undeclared variable 'missing'");
+    assert_eq!((diagnostics.diagnostics[0].span.start, diagnostics.diagnostics[0].span.end), (10, 17));
+  }
+
+  #[test]
+  pub fn should_insert_missing_tokens() {
+    let (_, tree) = lex_and_parse("let = 1");
+    let SyntaxTreeStatementKind::Let(let_statement) = &tree.statements[0].kind else {
+      panic!("Expected a let statement");
+    };
+    assert!(let_statement.identifier.is_missing());
+    assert_eq!((let_statement.identifier.span.start, let_statement.identifier.span.end), (3, 3));
+  }
+}
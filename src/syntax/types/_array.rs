@@ -0,0 +1,7 @@
+use crate::syntax::expression::SyntaxTreeExpression;
+
+// `[a, b, c]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArrayExpression {
+  pub elements: Vec<SyntaxTreeExpression>
+}
@@ -0,0 +1,26 @@
+use crate::syntax::{
+  expression::SyntaxTreeExpression,
+  lexer::Token,
+  types::_binary::BinaryOperatorKind
+};
+
+// `=` or a compound operator like `+=`, which applies its binary operator to
+// the current value of the target and the assigned value
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssignmentOperator {
+  pub kind: Option<BinaryOperatorKind>,
+  pub token: Token
+}
+
+impl AssignmentOperator {
+  pub fn new(kind: Option<BinaryOperatorKind>, token: Token) -> Self {
+    AssignmentOperator { kind, token }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssignmentExpression {
+  pub target: Box<SyntaxTreeExpression>,
+  pub operator: AssignmentOperator,
+  pub value: Box<SyntaxTreeExpression>
+}
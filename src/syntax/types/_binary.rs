@@ -1,16 +1,50 @@
-use crate::syntax::{expression::SyntaxTreeExpression, lexer::Token};
+use crate::syntax::{
+  expression::SyntaxTreeExpression,
+  lexer::Token,
+  operators::{self, Associativity}
+};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BinaryOperatorKind {
   Plus,
   Minus,
   Multiply,
   Divide,
+  Modulo,
   Power,
   BitwiseAnd,
   BitwiseOr,
-  BitwiseXor
+  BitwiseXor,
+  ShiftLeft,
+  ShiftRight,
+  Equal,
+  NotEqual,
+  LessThan,
+  LessThanOrEqual,
+  GreaterThan,
+  GreaterThanOrEqual,
+  LogicalAnd,
+  LogicalOr,
+  Range,
+  RangeInclusive,
+  // `step` after a range
+  Step
 }
+
+impl BinaryOperatorKind {
+  pub fn is_comparison(&self) -> bool {
+    return matches!(self,
+      BinaryOperatorKind::Equal | BinaryOperatorKind::NotEqual | BinaryOperatorKind::LessThan |
+      BinaryOperatorKind::LessThanOrEqual | BinaryOperatorKind::GreaterThan | BinaryOperatorKind::GreaterThanOrEqual
+    );
+  }
+
+  pub fn is_range(&self) -> bool {
+    return matches!(self, BinaryOperatorKind::Range | BinaryOperatorKind::RangeInclusive | BinaryOperatorKind::Step);
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct BinaryOperator {
   pub kind: BinaryOperatorKind,
   pub token: Token
@@ -22,18 +56,14 @@ impl BinaryOperator {
   }
 
   pub fn precedence(&self) -> u8 {
-    return match self.kind {
-      BinaryOperatorKind::Power => 20,      
-      BinaryOperatorKind::Multiply => 19,
-      BinaryOperatorKind::Divide => 19 ,      
-      BinaryOperatorKind::Plus => 18,
-      BinaryOperatorKind::Minus => 18,
-      BinaryOperatorKind::BitwiseAnd => 17,
-      BinaryOperatorKind::BitwiseXor => 16,
-      BinaryOperatorKind::BitwiseOr => 15
-    };
+    return operators::binary_operator(&self.kind).precedence;
+  }
+
+  pub fn associativity(&self) -> Associativity {
+    return operators::binary_operator(&self.kind).associativity;
   }
 }
+#[derive(Debug, Clone, PartialEq)]
 pub struct BinaryExpression {
   pub left: Box<SyntaxTreeExpression>,
   pub operator: BinaryOperator,
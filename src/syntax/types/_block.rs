@@ -0,0 +1,13 @@
+use crate::syntax::statement::{SyntaxTreeStatement, SyntaxTreeStatementKind};
+
+// A `{ ... }` block, its value is the value of its trailing expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockExpression {
+  pub statements: Vec<SyntaxTreeStatement>
+}
+
+impl BlockExpression {
+  pub fn is_expression(&self) -> bool {
+    return matches!(self.statements.last(), Some(SyntaxTreeStatement { kind: SyntaxTreeStatementKind::Expression(_), .. }));
+  }
+}
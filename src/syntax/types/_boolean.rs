@@ -0,0 +1,4 @@
+#[derive(Debug, Clone, PartialEq)]
+pub struct BooleanExpression {
+  pub value: bool
+}
@@ -0,0 +1,7 @@
+use crate::syntax::expression::SyntaxTreeExpression;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallExpression {
+  pub callee: Box<SyntaxTreeExpression>,
+  pub arguments: Vec<SyntaxTreeExpression>
+}
@@ -0,0 +1,11 @@
+use std::rc::Rc;
+
+use crate::syntax::{expression::SyntaxTreeExpression, lexer::Token};
+
+// `|a, b| body`, the body is any expression. Like the body of a function
+// declaration, it is shared with the function values made from it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClosureExpression {
+  pub parameters: Vec<Token>,
+  pub body: Rc<SyntaxTreeExpression>
+}
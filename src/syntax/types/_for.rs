@@ -0,0 +1,10 @@
+use crate::syntax::{expression::SyntaxTreeExpression, lexer::Token};
+
+// The loop variable is bound anew for every value of the iterable, in a scope
+// of its own around the body, which is always a block
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForStatement {
+  pub identifier: Token,
+  pub iterable: SyntaxTreeExpression,
+  pub body: SyntaxTreeExpression
+}
@@ -0,0 +1,12 @@
+use std::rc::Rc;
+
+use crate::syntax::{expression::SyntaxTreeExpression, lexer::Token};
+
+// The body is always a block. It is shared with the function values the
+// evaluator makes from the declaration, which outlive the walk of the tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionDeclaration {
+  pub identifier: Token,
+  pub parameters: Vec<Token>,
+  pub body: Rc<SyntaxTreeExpression>
+}
@@ -0,0 +1,10 @@
+use crate::syntax::expression::SyntaxTreeExpression;
+
+// The then branch is always a block, an else branch is a block or another if
+// expression for `else if`
+#[derive(Debug, Clone, PartialEq)]
+pub struct IfExpression {
+  pub condition: Box<SyntaxTreeExpression>,
+  pub then_branch: Box<SyntaxTreeExpression>,
+  pub else_branch: Option<Box<SyntaxTreeExpression>>
+}
@@ -0,0 +1,8 @@
+use crate::syntax::expression::SyntaxTreeExpression;
+
+// `target[index]`, an index that is a range takes a slice
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexExpression {
+  pub target: Box<SyntaxTreeExpression>,
+  pub index: Box<SyntaxTreeExpression>
+}
@@ -1,6 +1,9 @@
 use crate::syntax::{expression::SyntaxTreeExpression, lexer::Token};
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct LetStatement {
   pub identifier: Token,
+  // Declared with `let mut`, only then can the variable be assigned to
+  pub mutable: bool,
   pub initialiser: SyntaxTreeExpression
 }
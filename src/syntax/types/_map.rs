@@ -0,0 +1,14 @@
+use crate::syntax::expression::SyntaxTreeExpression;
+
+// `{ key: value, ... }`, `{:}` is the empty map. Keys are expressions, so
+// `{ name: 1 }` is keyed by the value of the variable `name`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapExpression {
+  pub entries: Vec<MapEntry>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapEntry {
+  pub key: SyntaxTreeExpression,
+  pub value: SyntaxTreeExpression
+}
@@ -1,4 +1,25 @@
-#[derive(Debug)]
+use std::fmt::{Display, Formatter};
+
+use num_bigint::BigInt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Number {
+  Integer(i64),
+  Float(f64),
+  BigInteger(BigInt)
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct NumberExpression {
-  pub number: i64
-}
\ No newline at end of file
+  pub number: Number
+}
+
+impl Display for Number {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Number::Integer(integer) => write!(f, "{}", integer),
+      Number::Float(float) => write!(f, "{:?}", float),
+      Number::BigInteger(integer) => write!(f, "{}n", integer)
+    }
+  }
+}
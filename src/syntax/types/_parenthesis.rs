@@ -1,5 +1,6 @@
 use crate::syntax::expression::SyntaxTreeExpression;
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct ParenthesisExpression {
   pub expression: Box<SyntaxTreeExpression>
 }
@@ -0,0 +1,8 @@
+use crate::syntax::{expression::SyntaxTreeExpression, lexer::Token};
+
+// Without a value the function returns unit
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReturnStatement {
+  pub keyword: Token,
+  pub value: Option<SyntaxTreeExpression>
+}
@@ -0,0 +1,17 @@
+use crate::syntax::expression::SyntaxTreeExpression;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StringExpression {
+  pub value: String
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterpolationPart {
+  Text(String),
+  Expression(SyntaxTreeExpression)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterpolationExpression {
+  pub parts: Vec<InterpolationPart>
+}
@@ -0,0 +1,28 @@
+use crate::syntax::{expression::SyntaxTreeExpression, lexer::Token};
+
+// `struct Point { x, y }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructDeclaration {
+  pub identifier: Token,
+  pub fields: Vec<Token>
+}
+
+// `Point { x: 1, y: 2 }`, the initialisers can come in any order
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructLiteralExpression {
+  pub identifier: Token,
+  pub initialisers: Vec<FieldInitialiser>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldInitialiser {
+  pub field: Token,
+  pub value: SyntaxTreeExpression
+}
+
+// `target.field`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldExpression {
+  pub target: Box<SyntaxTreeExpression>,
+  pub field: Token
+}
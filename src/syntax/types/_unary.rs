@@ -1,13 +1,16 @@
 use crate::syntax::{
   expression::SyntaxTreeExpression,
-  lexer::Token
+  lexer::Token,
+  operators
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum UnaryOperatorKind {
   Minus,
-  BitwiseNot
+  BitwiseNot,
+  LogicalNot
 }
+#[derive(Debug, Clone, PartialEq)]
 pub struct UnaryOperator {
   pub kind: UnaryOperatorKind,
   pub token: Token
@@ -16,8 +19,13 @@ pub struct UnaryOperator {
 impl UnaryOperator {
   pub fn new(kind: UnaryOperatorKind, token: Token) -> Self {
     UnaryOperator { kind, token}
-  }  
+  }
+
+  pub fn precedence(&self) -> u8 {
+    return operators::unary_operator(&self.kind).precedence;
+  }
 }
+#[derive(Debug, Clone, PartialEq)]
 pub struct UnaryExpression {
   pub operator: UnaryOperator,
   pub operand: Box<SyntaxTreeExpression>
@@ -1,5 +1,6 @@
 use crate::syntax::lexer::Token;
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct VariableExpression {
   pub identifier: Token
 }
@@ -0,0 +1,8 @@
+use crate::syntax::expression::SyntaxTreeExpression;
+
+// The body is always a block
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhileStatement {
+  pub condition: SyntaxTreeExpression,
+  pub body: SyntaxTreeExpression
+}
@@ -3,4 +3,18 @@ pub mod _number;
 pub mod _variable;
 pub mod _binary;
 pub mod _parenthesis;
-pub mod _unary;
\ No newline at end of file
+pub mod _unary;pub mod _string;
+pub mod _boolean;
+pub mod _block;
+pub mod _if;
+pub mod _while;
+pub mod _for;
+pub mod _assignment;
+pub mod _function;
+pub mod _return;
+pub mod _call;
+pub mod _closure;
+pub mod _array;
+pub mod _index;
+pub mod _map;
+pub mod _struct;
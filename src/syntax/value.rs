@@ -0,0 +1,301 @@
+use std::{collections::HashMap, fmt::{Display, Formatter}, rc::Rc};
+
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+
+use super::{builtins::Builtin, expression::SyntaxTreeExpression, types::_number::Number};
+
+// Runtime values produced by the Evaluator
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+  Integer(i64),
+  Float(f64),
+  BigInteger(BigInt),
+  String(String),
+  Boolean(bool),
+  Range(RangeValue),
+  Function(Rc<FunctionValue>),
+  Builtin(Builtin),
+  // Shared until one of the holders changes it, arrays are values like any other
+  Array(Rc<Vec<Value>>),
+  Map(Rc<MapValue>),
+  Struct(Rc<StructValue>),
+  // Value of an if expression without a taken branch and of a block without a trailing expression
+  Unit
+}
+
+impl Value {
+  pub fn type_name(&self) -> &'static str {
+    return match self {
+      Value::Integer(_) => "integer",
+      Value::Float(_) => "float",
+      Value::BigInteger(_) => "big integer",
+      Value::String(_) => "string",
+      Value::Boolean(_) => "boolean",
+      Value::Range(_) => "range",
+      Value::Function(_) | Value::Builtin(_) => "function",
+      Value::Array(_) => "array",
+      Value::Map(_) => "map",
+      Value::Struct(_) => "struct",
+      Value::Unit => "unit"
+    };
+  }
+
+  // Promotion rule for mixed arithmetic: an integer operand is widened to a
+  // float as soon as the other operand is a float.
+  pub fn as_float(&self) -> Option<f64> {
+    return match self {
+      Value::Integer(integer) => Some(*integer as f64),
+      Value::Float(float) => Some(*float),
+      Value::BigInteger(integer) => integer.to_f64(),
+      _ => None
+    };
+  }
+
+  // An integer mixed with a big integer is widened to a big integer
+  pub fn as_big_integer(&self) -> Option<BigInt> {
+    return match self {
+      Value::Integer(integer) => Some(BigInt::from(*integer)),
+      Value::BigInteger(integer) => Some(integer.clone()),
+      _ => None
+    };
+  }
+}
+
+// Integers from start towards end in steps of step, a negative step counts down
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeValue {
+  pub start: i64,
+  pub end: i64,
+  pub inclusive: bool,
+  pub step: i64
+}
+
+impl RangeValue {
+  pub fn new(start: i64, end: i64, inclusive: bool) -> Self {
+    return Self { start, end, inclusive, step: 1 };
+  }
+
+  pub fn is_empty(&self) -> bool {
+    return !self.contains(self.start);
+  }
+
+  // Whether a value hasn't gone past the end yet
+  fn contains(&self, value: i64) -> bool {
+    return match (self.step > 0, self.inclusive) {
+      (true, false) => value < self.end,
+      (true, true) => value <= self.end,
+      (false, false) => value > self.end,
+      (false, true) => value >= self.end
+    };
+  }
+
+  // Stops early rather than overflow past i64::MAX or i64::MIN
+  pub fn values(&self) -> impl Iterator<Item = i64> {
+    let range = self.clone();
+    let first = (!range.is_empty()).then_some(range.start);
+    return std::iter::successors(first, move |value| {
+      value.checked_add(range.step).filter(|next| range.contains(*next))
+    });
+  }
+}
+
+impl Display for RangeValue {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}{}{}", self.start, if self.inclusive { "..=" } else { ".." }, self.end)?;
+    if self.step != 1 {
+      write!(f, " step {}", self.step)?;
+    }
+    return Ok(());
+  }
+}
+
+// A declared function or a closure. A closure has no name and carries a copy
+// of the variables around it, taken when it was made.
+#[derive(Debug)]
+pub struct FunctionValue {
+  pub name: Option<String>,
+  pub parameters: Vec<String>,
+  pub body: Rc<SyntaxTreeExpression>,
+  pub captures: HashMap<String, Value>
+}
+
+// A function is only equal to itself
+impl PartialEq for FunctionValue {
+  fn eq(&self, other: &Self) -> bool {
+    return std::ptr::eq(self, other);
+  }
+}
+
+impl Value {
+  // Strings in an array, a map or a struct are quoted, so `["a, b"]` doesn't read as two elements
+  fn fmt_element(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    return match self {
+      Value::String(string) => write!(f, "{:?}", string),
+      _ => write!(f, "{}", self)
+    };
+  }
+}
+
+// The values a map can be keyed by
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MapKey {
+  Integer(i64),
+  String(String),
+  Boolean(bool)
+}
+
+impl MapKey {
+  // A big integer is the same key as the integer it equals, `1n` finds `1`
+  pub fn from_value(value: &Value) -> Option<MapKey> {
+    return match value {
+      Value::Integer(integer) => Some(MapKey::Integer(*integer)),
+      Value::BigInteger(integer) => integer.to_i64().map(MapKey::Integer),
+      Value::String(string) => Some(MapKey::String(string.clone())),
+      Value::Boolean(boolean) => Some(MapKey::Boolean(*boolean)),
+      _ => None
+    };
+  }
+}
+
+impl From<MapKey> for Value {
+  fn from(key: MapKey) -> Self {
+    return match key {
+      MapKey::Integer(integer) => Value::Integer(integer),
+      MapKey::String(string) => Value::String(string),
+      MapKey::Boolean(boolean) => Value::Boolean(boolean)
+    };
+  }
+}
+
+// Entries stay in the order their keys were first inserted in, so iterating
+// or printing a map gives the same order on every run
+#[derive(Debug, Clone, Default)]
+pub struct MapValue {
+  entries: Vec<(MapKey, Value)>,
+  positions: HashMap<MapKey, usize>
+}
+
+impl MapValue {
+  pub fn len(&self) -> usize {
+    return self.entries.len();
+  }
+
+  pub fn is_empty(&self) -> bool {
+    return self.entries.is_empty();
+  }
+
+  pub fn get(&self, key: &MapKey) -> Option<&Value> {
+    return self.positions.get(key).map(|position| &self.entries[*position].1);
+  }
+
+  pub fn get_mut(&mut self, key: &MapKey) -> Option<&mut Value> {
+    return self.positions.get(key).map(|position| &mut self.entries[*position].1);
+  }
+
+  pub fn contains_key(&self, key: &MapKey) -> bool {
+    return self.positions.contains_key(key);
+  }
+
+  // The value of a key already in the map, or unit under a new key at the end
+  pub fn get_or_insert(&mut self, key: MapKey) -> &mut Value {
+    let entries = &mut self.entries;
+    let position = *self.positions.entry(key.clone()).or_insert_with(|| {
+      entries.push((key, Value::Unit));
+      entries.len() - 1
+    });
+    return &mut self.entries[position].1;
+  }
+
+  pub fn insert(&mut self, key: MapKey, value: Value) {
+    *self.get_or_insert(key) = value;
+  }
+
+  pub fn entries(&self) -> impl Iterator<Item = (&MapKey, &Value)> {
+    return self.entries.iter().map(|(key, value)| (key, value));
+  }
+}
+
+// Maps with the same entries are equal, whatever order they were inserted in
+impl PartialEq for MapValue {
+  fn eq(&self, other: &Self) -> bool {
+    return self.len() == other.len() && self.entries().all(|(key, value)| other.get(key) == Some(value));
+  }
+}
+
+// An instance of a declared struct, its fields in the order they are declared in
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructValue {
+  pub name: String,
+  pub fields: Vec<(String, Value)>
+}
+
+impl StructValue {
+  pub fn get(&self, field: &str) -> Option<&Value> {
+    return self.fields.iter().find(|(name, _)| name == field).map(|(_, value)| value);
+  }
+
+  pub fn get_mut(&mut self, field: &str) -> Option<&mut Value> {
+    return self.fields.iter_mut().find(|(name, _)| name == field).map(|(_, value)| value);
+  }
+}
+
+impl From<Number> for Value {
+  fn from(number: Number) -> Self {
+    return match number {
+      Number::Integer(integer) => Value::Integer(integer),
+      Number::Float(float) => Value::Float(float),
+      Number::BigInteger(integer) => Value::BigInteger(integer)
+    };
+  }
+}
+
+impl Display for Value {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Value::Integer(integer) => write!(f, "{}", integer),
+      Value::Float(float) => write!(f, "{:?}", float),
+      Value::BigInteger(integer) => write!(f, "{}", integer),
+      Value::String(string) => write!(f, "{}", string),
+      Value::Boolean(boolean) => write!(f, "{}", boolean),
+      Value::Range(range) => write!(f, "{}", range),
+      Value::Function(function) => match &function.name {
+        Some(name) => write!(f, "<fn {}>", name),
+        None => write!(f, "<closure>")
+      },
+      Value::Builtin(builtin) => write!(f, "<fn {}>", builtin.name()),
+      Value::Array(elements) => {
+        write!(f, "[")?;
+        for (index, element) in elements.iter().enumerate() {
+          if index > 0 {
+            write!(f, ", ")?;
+          }
+          element.fmt_element(f)?;
+        }
+        write!(f, "]")
+      },
+      Value::Map(map) if map.is_empty() => write!(f, "{{:}}"),
+      Value::Map(map) => {
+        write!(f, "{{")?;
+        for (index, (key, value)) in map.entries().enumerate() {
+          if index > 0 {
+            write!(f, ", ")?;
+          }
+          Value::from(key.clone()).fmt_element(f)?;
+          write!(f, ": ")?;
+          value.fmt_element(f)?;
+        }
+        write!(f, "}}")
+      },
+      Value::Struct(instance) => {
+        write!(f, "{} {{", instance.name)?;
+        for (index, (field, value)) in instance.fields.iter().enumerate() {
+          write!(f, "{}{}: ", if index > 0 { ", " } else { " " }, field)?;
+          value.fmt_element(f)?;
+        }
+        write!(f, "{}}}", if instance.fields.is_empty() { "" } else { " " })
+      },
+      Value::Unit => write!(f, "()")
+    }
+  }
+}
@@ -1,13 +1,29 @@
+use super::ensure_stack;
 use super::expression::SyntaxTreeExpressionKind;
 use super::expression::SyntaxTreeExpression;
-use super::lexer::TextSpan;
+use super::lexer::{TextSpan, Token};
 use super::statement::{SyntaxTreeStatement, SyntaxTreeStatementKind};
+use super::types::_assignment::AssignmentExpression;
 use super::types::_binary::BinaryExpression;
+use super::types::_block::BlockExpression;
+use super::types::_boolean::BooleanExpression;
+use super::types::_call::CallExpression;
+use super::types::_closure::ClosureExpression;
+use super::types::_array::ArrayExpression;
+use super::types::_index::IndexExpression;
+use super::types::_map::MapExpression;
+use super::types::_struct::{FieldExpression, StructDeclaration, StructLiteralExpression};
+use super::types::_for::ForStatement;
+use super::types::_function::FunctionDeclaration;
+use super::types::_if::IfExpression;
 use super::types::_number::NumberExpression;
 use super::types::_let::LetStatement;
 use super::types::_parenthesis::ParenthesisExpression;
+use super::types::_return::ReturnStatement;
+use super::types::_string::{InterpolationExpression, InterpolationPart, StringExpression};
 use super::types::_unary::UnaryExpression;
 use super::types::_variable::VariableExpression;
+use super::types::_while::WhileStatement;
 
 pub trait SyntaxTreeVisitor {
   fn do_visit_statement(&mut self, statement: &SyntaxTreeStatement) {
@@ -18,6 +34,27 @@ pub trait SyntaxTreeVisitor {
       SyntaxTreeStatementKind::Let(expr) => {
         self.visit_let_statement(expr);
       }
+      SyntaxTreeStatementKind::Function(expr) => {
+        self.visit_function_declaration(expr);
+      }
+      SyntaxTreeStatementKind::Return(expr) => {
+        self.visit_return_statement(expr);
+      }
+      SyntaxTreeStatementKind::Struct(expr) => {
+        self.visit_struct_declaration(expr);
+      }
+      SyntaxTreeStatementKind::While(expr) => {
+        self.visit_while_statement(expr);
+      }
+      SyntaxTreeStatementKind::For(expr) => {
+        self.visit_for_statement(expr);
+      }
+      SyntaxTreeStatementKind::Break(keyword) => {
+        self.visit_break_statement(keyword);
+      }
+      SyntaxTreeStatementKind::Continue(keyword) => {
+        self.visit_continue_statement(keyword);
+      }
     }  
   }
 
@@ -26,11 +63,41 @@ pub trait SyntaxTreeVisitor {
     self.do_visit_statement(statement)
   }
 
+  fn visit_function_declaration(&mut self, function_declaration: &FunctionDeclaration) {
+    self.visit_expression(&function_declaration.body);
+  }
+
+  fn visit_struct_declaration(&mut self, _struct_declaration: &StructDeclaration) {
+  }
+
+  fn visit_return_statement(&mut self, return_statement: &ReturnStatement) {
+    if let Some(value) = &return_statement.value {
+      self.visit_expression(value);
+    }
+  }
+
+  fn visit_while_statement(&mut self, while_statement: &WhileStatement) {
+    self.visit_expression(&while_statement.condition);
+    self.visit_expression(&while_statement.body);
+  }
+
+  fn visit_for_statement(&mut self, for_statement: &ForStatement) {
+    self.visit_expression(&for_statement.iterable);
+    self.visit_expression(&for_statement.body);
+  }
+
+  fn visit_break_statement(&mut self, _keyword: &Token) {}
+
+  fn visit_continue_statement(&mut self, _keyword: &Token) {}
+
   fn do_visit_expression(&mut self, expression: &SyntaxTreeExpression) {
     match &expression.kind {
       SyntaxTreeExpressionKind::Number(number) => {
         self.visit_number(number);
       }
+      SyntaxTreeExpressionKind::Boolean(boolean) => {
+        self.visit_boolean(boolean);
+      }
       SyntaxTreeExpressionKind::Binary(expr) => {
         self.visit_binary_expression(expr);
       }
@@ -40,25 +107,80 @@ pub trait SyntaxTreeVisitor {
       SyntaxTreeExpressionKind::Parenthesised(expr) => {
         self.visit_parenthesised_expression(expr);
       }
+      SyntaxTreeExpressionKind::Block(expr) => {
+        self.visit_block_expression(expr);
+      }
+      SyntaxTreeExpressionKind::If(expr) => {
+        self.visit_if_expression(expr);
+      }
+      SyntaxTreeExpressionKind::Assignment(expr) => {
+        self.visit_assignment_expression(expr);
+      }
+      SyntaxTreeExpressionKind::Call(expr) => {
+        self.visit_call_expression(expr);
+      }
+      SyntaxTreeExpressionKind::Closure(expr) => {
+        self.visit_closure_expression(expr);
+      }
+      SyntaxTreeExpressionKind::Array(expr) => {
+        self.visit_array_expression(expr);
+      }
+      SyntaxTreeExpressionKind::Index(expr) => {
+        self.visit_index_expression(expr);
+      }
+      SyntaxTreeExpressionKind::Map(expr) => {
+        self.visit_map_expression(expr);
+      }
+      SyntaxTreeExpressionKind::StructLiteral(expr) => {
+        self.visit_struct_literal_expression(expr);
+      }
+      SyntaxTreeExpressionKind::Field(expr) => {
+        self.visit_field_expression(expr);
+      }
       SyntaxTreeExpressionKind::Error(span) => {
         self.visit_error(span);
       }
+      SyntaxTreeExpressionKind::Missing(span) => {
+        self.visit_missing(span);
+      }
       SyntaxTreeExpressionKind::Variable(expr) => {
         self.visit_variable_expression(expr);
       }
+      SyntaxTreeExpressionKind::String(expr) => {
+        self.visit_string(expr);
+      }
+      SyntaxTreeExpressionKind::Interpolation(expr) => {
+        self.visit_interpolation_expression(expr);
+      }
     }
   }
 
   fn visit_expression(&mut self, expression: &SyntaxTreeExpression) {
-    self.do_visit_expression(expression);
+    ensure_stack(|| self.do_visit_expression(expression));
   }
 
   fn visit_number(&mut self, number: &NumberExpression);
 
+  fn visit_boolean(&mut self, _boolean: &BooleanExpression) {}
+
   fn visit_variable_expression(&mut self, variable_expression: &VariableExpression);
 
+  fn visit_string(&mut self, _string: &StringExpression) {}
+
+  fn visit_interpolation_expression(&mut self, interpolation_expression: &InterpolationExpression) {
+    for part in &interpolation_expression.parts {
+      if let InterpolationPart::Expression(expr) = part {
+        self.visit_expression(expr);
+      }
+    }
+  }
+
   fn visit_error(&mut self, span: &TextSpan);
 
+  fn visit_missing(&mut self, span: &TextSpan) {
+    self.visit_error(span);
+  }
+
   fn visit_binary_expression(&mut self, binary_expression: &BinaryExpression) {
     self.visit_expression(&binary_expression.left);
     self.visit_expression(&binary_expression.right);
@@ -68,5 +190,63 @@ pub trait SyntaxTreeVisitor {
 
   fn visit_parenthesised_expression(&mut self, parenthesised_expression: &ParenthesisExpression) {
     self.visit_expression(&parenthesised_expression.expression);
+  }
+
+  fn visit_assignment_expression(&mut self, assignment_expression: &AssignmentExpression) {
+    self.visit_expression(&assignment_expression.target);
+    self.visit_expression(&assignment_expression.value);
+  }
+
+  fn visit_call_expression(&mut self, call_expression: &CallExpression) {
+    self.visit_expression(&call_expression.callee);
+    for argument in &call_expression.arguments {
+      self.visit_expression(argument);
+    }
+  }
+
+  fn visit_closure_expression(&mut self, closure_expression: &ClosureExpression) {
+    self.visit_expression(&closure_expression.body);
+  }
+
+  fn visit_array_expression(&mut self, array_expression: &ArrayExpression) {
+    for element in &array_expression.elements {
+      self.visit_expression(element);
+    }
+  }
+
+  fn visit_index_expression(&mut self, index_expression: &IndexExpression) {
+    self.visit_expression(&index_expression.target);
+    self.visit_expression(&index_expression.index);
+  }
+
+  fn visit_map_expression(&mut self, map_expression: &MapExpression) {
+    for entry in &map_expression.entries {
+      self.visit_expression(&entry.key);
+      self.visit_expression(&entry.value);
+    }
+  }
+
+  fn visit_struct_literal_expression(&mut self, struct_literal_expression: &StructLiteralExpression) {
+    for initialiser in &struct_literal_expression.initialisers {
+      self.visit_expression(&initialiser.value);
+    }
+  }
+
+  fn visit_field_expression(&mut self, field_expression: &FieldExpression) {
+    self.visit_expression(&field_expression.target);
+  }
+
+  fn visit_block_expression(&mut self, block_expression: &BlockExpression) {
+    for statement in &block_expression.statements {
+      self.visit_statement(statement);
+    }
+  }
+
+  fn visit_if_expression(&mut self, if_expression: &IfExpression) {
+    self.visit_expression(&if_expression.condition);
+    self.visit_expression(&if_expression.then_branch);
+    if let Some(else_branch) = &if_expression.else_branch {
+      self.visit_expression(else_branch);
+    }
   }   
 }
\ No newline at end of file
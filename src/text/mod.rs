@@ -1,21 +1,53 @@
+use std::ops::Range;
+
 pub struct SourceText {
-  text: String
+  text: String,
+  // Byte offset at which every line starts, the first line always starts at 0
+  line_starts: Vec<usize>
 }
 
 impl SourceText {
   pub fn new(text: String) -> Self {
-    return Self { text };
+    let line_starts = std::iter::once(0).chain(
+      text.match_indices('\n').map(|(index, _)| index + 1)
+    ).collect();
+    return Self { text, line_starts };
   }
 
+  // Spans are byte offsets, so positions here are byte offsets into the text
   pub fn line_index(&self, position: usize) -> usize {
-    self.text[..position].lines().count() - 1
+    return self.line_starts.partition_point(|start| *start <= position) - 1;
   }
 
   pub fn get_line(&self, index: usize) -> &str {
-    return self.text.lines().nth(index).unwrap();
+    let end = self.line_starts.get(index + 1).map_or(self.text.len(), |start| start - 1);
+    return self.text[self.line_starts[index]..end].trim_end_matches('\r');
   }
 
   pub fn line_start(&self, index: usize) -> usize {
-    return self.text.lines().take(index).map(|line| line.len() + 1).sum();
+    return self.line_starts[index];
+  }
+}
+
+// Replaces the byte range of a text, as sent by an editor on every keystroke
+pub struct TextEdit {
+  pub range: Range<usize>,
+  pub replacement: String
+}
+
+impl TextEdit {
+  pub fn new(range: Range<usize>, replacement: &str) -> Self {
+    return Self { range, replacement: replacement.to_string() };
   }
-}
\ No newline at end of file
+
+  pub fn apply(&self, text: &str) -> String {
+    let mut result = text.to_string();
+    result.replace_range(self.range.clone(), &self.replacement);
+    return result;
+  }
+
+  // How far text after the edited range moves
+  pub fn delta(&self) -> isize {
+    return self.replacement.len() as isize - self.range.len() as isize;
+  }
+}